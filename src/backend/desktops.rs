@@ -0,0 +1,26 @@
+//! The part of the window manager state that backends
+//! publish to other clients, e.g. pagers, bars and `wmctrl`.
+
+use backend::WindowState;
+use core::Rectangle;
+
+/// The window manager state as seen by other clients. Desktops
+/// are the workspaces, numbered by their id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Desktops<Window> {
+    /// The names of all desktops, ordered by id
+    pub names: Vec<String>,
+    /// The id of the focused desktop
+    pub current: u32,
+    /// The area available to windows on every desktop
+    pub workarea: Rectangle,
+    /// All managed windows together with the id of their
+    /// desktop, in the order they got managed
+    pub clients: Vec<(Window, u32)>,
+    /// All managed windows from bottom to top
+    pub stacking: Vec<Window>,
+    /// The focused window, if any
+    pub active: Option<Window>,
+    /// The states set on the managed windows
+    pub states: Vec<(Window, WindowState)>,
+}
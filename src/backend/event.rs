@@ -3,8 +3,31 @@
 //! new windows got created, a window got closed or if
 //! some user input happened.
 
+use backend::KeyCommand;
 use core::Rectangle;
 
+/// The states of a window other clients can ask for
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WindowState {
+    /// The window covers its whole screen, without a border
+    Fullscreen,
+    /// The window needs the user's attention
+    DemandsAttention,
+}
+
+/// How a [`WindowState`] should be changed
+///
+/// [`WindowState`]: enum.WindowState.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StateChange {
+    /// Unset the state
+    Remove,
+    /// Set the state
+    Add,
+    /// Set the state if it is not set, unset it otherwise
+    Toggle,
+}
+
 /// A cross-section of all events that can be generated/handled
 /// by xlib, xcb and wayland.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -14,12 +37,19 @@ pub enum Event<Window> {
     BackendChanged,
     /// A new window has been created
     WindowCreated(Window),
-    /// A window has been closed/killed
+    /// A window has been closed/killed or withdrawn by its client
     WindowClosed(Window),
-    /// A window has been hid, but is still around
+    /// A window has been hid by the window manager, but is still around
     WindowHid(Window),
     /// A windowhas been revealed from hidden status
     WindowRevealed(Window),
+    /// The title of a window changed
+    WindowTitleChanged(Window),
+    /// The hints of a window changed, e.g. it became urgent
+    /// or supports different sizes now
+    WindowHintsChanged(Window),
+    /// The space a dock reserves at the screen edges changed
+    StrutsChanged(Window),
     /// A window/app is requesting a change in size
     WindowChangeRequest(Window, Rectangle),
     /// The mouse pointer has entered a window's
@@ -31,8 +61,19 @@ pub enum Event<Window> {
     ButtonPressed(Window, Option<Window>),
     /// A button has been released
     ButtonReleased,
-    /// A key has been pressed
-    KeyPressed(Window),
+    /// A key has been pressed while the given window was focused.
+    /// Lock modifiers like caps lock and num lock are already removed.
+    KeyPressed(Window, KeyCommand),
+    /// Another client, e.g. a pager, asks to focus the window
+    ActivateRequest(Window),
+    /// Another client asks to close the window
+    CloseRequest(Window),
+    /// Another client asks to switch to the workspace with the given id
+    WorkspaceRequest(u32),
+    /// Another client asks to move the window to the workspace with the given id
+    MoveRequest(Window, u32),
+    /// Another client or the window itself asks to change a state of the window
+    StateRequest(Window, StateChange, WindowState),
     /// An unknown or not important event
     Unknown,
 }
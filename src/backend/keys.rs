@@ -0,0 +1,261 @@
+//! Keys are described the same way X11 and xkbcommon describe them:
+//! a keysym identifying the symbol on the key and a modifier mask.
+//! This allows to write key bindings as plain strings like
+//! `"Mod4+Shift+Return"`, independent of any keyboard layout.
+
+use errors::*;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Modifier mask for the shift key
+pub const MOD_SHIFT: u16 = 0x01;
+/// Modifier mask for caps lock
+pub const MOD_LOCK: u16 = 0x02;
+/// Modifier mask for the control key
+pub const MOD_CONTROL: u16 = 0x04;
+/// Modifier mask for mod1, usually the alt key
+pub const MOD_1: u16 = 0x08;
+/// Modifier mask for mod2, usually num lock
+pub const MOD_2: u16 = 0x10;
+/// Modifier mask for mod3
+pub const MOD_3: u16 = 0x20;
+/// Modifier mask for mod4, usually the super/windows key
+pub const MOD_4: u16 = 0x40;
+/// Modifier mask for mod5
+pub const MOD_5: u16 = 0x80;
+
+/// The names of the modifiers, as used in key binding specifications
+const MODIFIERS: &[(&str, u16)] = &[("Shift", MOD_SHIFT),
+                                    ("Lock", MOD_LOCK),
+                                    ("Control", MOD_CONTROL),
+                                    ("Ctrl", MOD_CONTROL),
+                                    ("Mod1", MOD_1),
+                                    ("Alt", MOD_1),
+                                    ("Mod2", MOD_2),
+                                    ("Mod3", MOD_3),
+                                    ("Mod4", MOD_4),
+                                    ("Super", MOD_4),
+                                    ("Mod5", MOD_5)];
+
+/// Keysyms of non-printable and special keys. Printable ASCII
+/// characters are their own keysym and don't need to be listed.
+const KEYSYMS: &[(&str, u32)] = &[("space", 0x0020),
+                                  ("exclam", 0x0021),
+                                  ("quotedbl", 0x0022),
+                                  ("numbersign", 0x0023),
+                                  ("dollar", 0x0024),
+                                  ("percent", 0x0025),
+                                  ("ampersand", 0x0026),
+                                  ("apostrophe", 0x0027),
+                                  ("parenleft", 0x0028),
+                                  ("parenright", 0x0029),
+                                  ("asterisk", 0x002a),
+                                  ("plus", 0x002b),
+                                  ("comma", 0x002c),
+                                  ("minus", 0x002d),
+                                  ("period", 0x002e),
+                                  ("slash", 0x002f),
+                                  ("colon", 0x003a),
+                                  ("semicolon", 0x003b),
+                                  ("less", 0x003c),
+                                  ("equal", 0x003d),
+                                  ("greater", 0x003e),
+                                  ("question", 0x003f),
+                                  ("at", 0x0040),
+                                  ("bracketleft", 0x005b),
+                                  ("backslash", 0x005c),
+                                  ("bracketright", 0x005d),
+                                  ("asciicircum", 0x005e),
+                                  ("underscore", 0x005f),
+                                  ("grave", 0x0060),
+                                  ("braceleft", 0x007b),
+                                  ("bar", 0x007c),
+                                  ("braceright", 0x007d),
+                                  ("asciitilde", 0x007e),
+                                  ("BackSpace", 0xff08),
+                                  ("Tab", 0xff09),
+                                  ("Return", 0xff0d),
+                                  ("Pause", 0xff13),
+                                  ("Scroll_Lock", 0xff14),
+                                  ("Escape", 0xff1b),
+                                  ("Home", 0xff50),
+                                  ("Left", 0xff51),
+                                  ("Up", 0xff52),
+                                  ("Right", 0xff53),
+                                  ("Down", 0xff54),
+                                  ("Prior", 0xff55),
+                                  ("Page_Up", 0xff55),
+                                  ("Next", 0xff56),
+                                  ("Page_Down", 0xff56),
+                                  ("End", 0xff57),
+                                  ("Print", 0xff61),
+                                  ("Insert", 0xff63),
+                                  ("Menu", 0xff67),
+                                  ("Num_Lock", 0xff7f),
+                                  ("KP_Enter", 0xff8d),
+                                  ("F1", 0xffbe),
+                                  ("F2", 0xffbf),
+                                  ("F3", 0xffc0),
+                                  ("F4", 0xffc1),
+                                  ("F5", 0xffc2),
+                                  ("F6", 0xffc3),
+                                  ("F7", 0xffc4),
+                                  ("F8", 0xffc5),
+                                  ("F9", 0xffc6),
+                                  ("F10", 0xffc7),
+                                  ("F11", 0xffc8),
+                                  ("F12", 0xffc9),
+                                  ("Shift_L", 0xffe1),
+                                  ("Shift_R", 0xffe2),
+                                  ("Control_L", 0xffe3),
+                                  ("Control_R", 0xffe4),
+                                  ("Caps_Lock", 0xffe5),
+                                  ("Alt_L", 0xffe9),
+                                  ("Alt_R", 0xffea),
+                                  ("Super_L", 0xffeb),
+                                  ("Super_R", 0xffec),
+                                  ("Delete", 0xffff),
+                                  ("XF86MonBrightnessUp", 0x1008ff02),
+                                  ("XF86MonBrightnessDown", 0x1008ff03),
+                                  ("XF86AudioLowerVolume", 0x1008ff11),
+                                  ("XF86AudioMute", 0x1008ff12),
+                                  ("XF86AudioRaiseVolume", 0x1008ff13),
+                                  ("XF86AudioPlay", 0x1008ff14),
+                                  ("XF86AudioStop", 0x1008ff15),
+                                  ("XF86AudioPrev", 0x1008ff16),
+                                  ("XF86AudioNext", 0x1008ff17),
+                                  ("XF86AudioMicMute", 0x1008ffb2)];
+
+/// Keysym of the num lock key, needed to find its modifier
+pub(crate) const KEYSYM_NUM_LOCK: u32 = 0xff7f;
+
+/// Look up the keysym for the given name, e.g. `"Return"` or `"a"`.
+/// Single uppercase letters are treated as their lowercase counterpart,
+/// use the `Shift` modifier to bind the uppercase letter.
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::backend::keysym_from_name;
+/// assert_eq!(Some(0xff0d), keysym_from_name("Return"));
+/// assert_eq!(Some(0x61), keysym_from_name("a"));
+/// assert_eq!(Some(0x61), keysym_from_name("A"));
+/// assert_eq!(Some(0x31), keysym_from_name("1"));
+/// assert_eq!(Some(0xff0d), keysym_from_name("0xff0d"));
+/// assert_eq!(None, keysym_from_name("NoSuchKey"));
+/// ```
+///
+/// # Arguments
+/// `name` - The name of the key or a hexadecimal keysym
+///
+/// # Return value
+/// The keysym or `None` if the name is unknown
+pub fn keysym_from_name(name: &str) -> Option<u32> {
+    if let Some(&(_, keysym)) = KEYSYMS.iter().find(|&&(n, _)| n == name) {
+        return Some(keysym);
+    }
+
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_graphic() => Some(c.to_ascii_lowercase() as u32),
+        _ if name.starts_with("0x") => u32::from_str_radix(&name[2..], 16).ok(),
+        _ => None,
+    }
+}
+
+/// Look up the name of the given keysym
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::backend::keysym_name;
+/// assert_eq!("Return", keysym_name(0xff0d));
+/// assert_eq!("a", keysym_name(0x61));
+/// assert_eq!("0x1234567", keysym_name(0x1234567));
+/// ```
+pub fn keysym_name(keysym: u32) -> String {
+    match KEYSYMS.iter().find(|&&(_, k)| k == keysym) {
+        Some(&(name, _)) => String::from(name),
+        None if keysym > 0x20 && keysym < 0x7f => (keysym as u8 as char).to_string(),
+        None => format!("{:#x}", keysym),
+    }
+}
+
+/// A key combination: a keysym together with the modifiers
+/// that need to be held down.
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::backend::{KeyCommand, MOD_4, MOD_SHIFT};
+/// let command: KeyCommand = "Mod4+Shift+Return".parse().unwrap();
+/// assert_eq!(KeyCommand::new(0xff0d, MOD_4 | MOD_SHIFT), command);
+/// assert_eq!("Shift+Mod4+Return", command.to_string());
+///
+/// assert!("Mod4+NoSuchKey".parse::<KeyCommand>().is_err());
+/// assert!("Hyper+a".parse::<KeyCommand>().is_err());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyCommand {
+    /// The keysym of the pressed key
+    pub keysym: u32,
+    /// The modifier mask, see the `MOD_*` constants
+    pub modifiers: u16,
+}
+
+impl KeyCommand {
+    /// Create a new [`KeyCommand`] from a keysym and a modifier mask
+    ///
+    /// [`KeyCommand`]: struct.KeyCommand.html
+    pub fn new(keysym: u32, modifiers: u16) -> KeyCommand {
+        KeyCommand { keysym, modifiers }
+    }
+}
+
+impl FromStr for KeyCommand {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<KeyCommand> {
+        let mut parts: Vec<&str> = spec.split('+').map(|s| s.trim()).collect();
+        let key = parts.pop().unwrap_or("");
+        let keysym = keysym_from_name(key)
+            .ok_or_else(|| format!("unknown key {:?} in key binding {:?}", key, spec))?;
+
+        let mut modifiers = 0;
+        for part in parts {
+            let modifier = MODIFIERS.iter()
+                .find(|&&(name, _)| name.eq_ignore_ascii_case(part))
+                .ok_or_else(|| format!("unknown modifier {:?} in key binding {:?}", part, spec))?;
+            modifiers |= modifier.1;
+        }
+
+        Ok(KeyCommand::new(keysym, modifiers))
+    }
+}
+
+impl fmt::Display for KeyCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut seen = 0;
+        for &(name, mask) in MODIFIERS {
+            if self.modifiers & mask != 0 && seen & mask == 0 {
+                write!(f, "{}+", name)?;
+                seen |= mask;
+            }
+        }
+        write!(f, "{}", keysym_name(self.keysym))
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyCommand {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for KeyCommand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
@@ -0,0 +1,199 @@
+//! A [`Backend`] that only keeps its windows in memory,
+//! so the window manager can be tested without a display.
+//!
+//! [`Backend`]: trait.Backend.html
+
+use backend::{Backend, Desktops, Event, KeyCommand, WindowType};
+use core::{Rectangle, SizeHints, Strut};
+use errors::*;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::os::fd::{AsFd, BorrowedFd};
+
+/// The size of windows that were never moved or resized
+const DEFAULT_SIZE: u32 = 100;
+
+/// The in-memory [`Backend`]. Tests describe the windows through
+/// the public fields and check how the window manager treated them.
+///
+/// [`Backend`]: trait.Backend.html
+pub(crate) struct MockBackend {
+    pub(crate) screens: Vec<Rectangle>,
+    pub(crate) window_types: RefCell<HashMap<u32, WindowType>>,
+    pub(crate) transients: RefCell<HashMap<u32, u32>>,
+    pub(crate) struts: RefCell<HashMap<u32, Vec<Strut>>>,
+    pub(crate) geometry: RefCell<HashMap<u32, Rectangle>>,
+    pub(crate) visible: RefCell<HashSet<u32>>,
+    pub(crate) focused: Cell<Option<u32>>,
+    pub(crate) closed: RefCell<Vec<u32>>,
+    pub(crate) desktops: RefCell<Option<Desktops<u32>>>,
+    pub(crate) events: RefCell<VecDeque<Event<u32>>>,
+    /// Never readable, events are taken from `events` instead
+    null: File,
+}
+
+impl MockBackend {
+    /// Create a backend with the given screens and no windows
+    pub(crate) fn with_screens(screens: Vec<Rectangle>) -> MockBackend {
+        MockBackend {
+            screens,
+            window_types: RefCell::new(HashMap::new()),
+            transients: RefCell::new(HashMap::new()),
+            struts: RefCell::new(HashMap::new()),
+            geometry: RefCell::new(HashMap::new()),
+            visible: RefCell::new(HashSet::new()),
+            focused: Cell::new(None),
+            closed: RefCell::new(Vec::new()),
+            desktops: RefCell::new(None),
+            events: RefCell::new(VecDeque::new()),
+            null: File::open("/dev/null").expect("unable to open /dev/null"),
+        }
+    }
+
+    /// The current geometry of the given window
+    pub(crate) fn geometry_of(&self, window: u32) -> Rectangle {
+        self.geometry
+            .borrow()
+            .get(&window)
+            .cloned()
+            .unwrap_or_else(|| Rectangle::new(0, 0, DEFAULT_SIZE, DEFAULT_SIZE))
+    }
+
+    /// Whether the given window is shown
+    pub(crate) fn is_visible(&self, window: u32) -> bool {
+        self.visible.borrow().contains(&window)
+    }
+}
+
+impl Backend for MockBackend {
+    type Window = u32;
+
+    fn new() -> Result<MockBackend> {
+        Ok(MockBackend::with_screens(vec![Rectangle::new(0, 0, 800, 600)]))
+    }
+
+    fn transient_for(&self, window: u32) -> Option<u32> {
+        self.transients.borrow().get(&window).cloned()
+    }
+
+    fn window_group(&self, _: u32) -> Option<u32> {
+        None
+    }
+
+    fn struts(&self, window: u32) -> Vec<Strut> {
+        self.struts.borrow().get(&window).cloned().unwrap_or_default()
+    }
+
+    fn window_type(&self, window: u32) -> WindowType {
+        self.window_types.borrow().get(&window).cloned().unwrap_or_default()
+    }
+
+    fn is_window(&self, _: u32) -> bool {
+        true
+    }
+
+    fn screens(&self) -> Vec<Rectangle> {
+        self.screens.clone()
+    }
+
+    fn number_of_screens(&self) -> usize {
+        self.screens.len()
+    }
+
+    fn window_name(&self, window: u32) -> Result<String> {
+        Ok(format!("window {}", window))
+    }
+
+    fn class_name(&self, window: u32) -> Result<String> {
+        bail!("window {} has no class", window)
+    }
+
+    fn is_urgent(&self, _: u32) -> bool {
+        false
+    }
+
+    fn size_hints(&self, _: u32) -> SizeHints {
+        SizeHints::default()
+    }
+
+    fn is_withdrawn(&self, _: u32) -> bool {
+        false
+    }
+
+    fn window_desktop(&self, _: u32) -> Option<u32> {
+        None
+    }
+
+    fn adopt_window(&self, _: u32) {}
+
+    fn windows(&self) -> Result<Vec<u32>> {
+        Ok(Vec::new())
+    }
+
+    fn resize_window(&self, window: u32, width: u32, height: u32) {
+        let current = self.geometry_of(window);
+        self.geometry
+            .borrow_mut()
+            .insert(window, Rectangle::new(current.x(), current.y(), width, height));
+    }
+
+    fn move_window(&self, window: u32, x: i32, y: i32) {
+        let current = self.geometry_of(window);
+        self.geometry
+            .borrow_mut()
+            .insert(window, Rectangle::new(x, y, current.width(), current.height()));
+    }
+
+    fn show_window(&self, window: u32) {
+        self.visible.borrow_mut().insert(window);
+    }
+
+    fn hide_window(&self, window: u32) {
+        self.visible.borrow_mut().remove(&window);
+    }
+
+    fn raise_window(&self, _: u32) {}
+
+    fn lower_window(&self, _: u32) {}
+
+    fn close_window(&self, window: u32) {
+        self.closed.borrow_mut().push(window);
+    }
+
+    fn kill_window(&self, window: u32) {
+        self.closed.borrow_mut().push(window);
+    }
+
+    fn focus_window(&self, window: u32) {
+        self.focused.set(Some(window));
+    }
+
+    fn grab_keys(&self, _: &[KeyCommand]) {}
+
+    fn set_window_border(&self, _: u32, _: u32, _: u32) {}
+
+    fn window_geometry(&self, window: u32) -> Result<Rectangle> {
+        Ok(self.geometry_of(window))
+    }
+
+    fn set_root_property(&self, _: &str, _: Option<&str>) {}
+
+    fn publish_desktops(&self, desktops: &Desktops<u32>) {
+        *self.desktops.borrow_mut() = Some(desktops.clone());
+    }
+
+    fn event(&self) -> Event<u32> {
+        self.poll_event().unwrap_or(Event::Unknown)
+    }
+
+    fn poll_event(&self) -> Option<Event<u32>> {
+        self.events.borrow_mut().pop_front()
+    }
+}
+
+impl AsFd for MockBackend {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.null.as_fd()
+    }
+}
@@ -6,25 +6,49 @@
 //! that can be implemented for different kinds of backends,
 //! e.g. XCB.
 
+mod desktops;
 mod event;
+mod keys;
+#[cfg(test)]
+mod mock;
+mod window_type;
 mod xcb;
 
-pub use backend::event::Event;
+pub use backend::desktops::Desktops;
+pub use backend::event::{Event, StateChange, WindowState};
+pub use backend::keys::{KeyCommand, MOD_1, MOD_2, MOD_3, MOD_4, MOD_5, MOD_CONTROL, MOD_LOCK,
+                        MOD_SHIFT, keysym_from_name, keysym_name};
+#[cfg(test)]
+pub(crate) use backend::mock::MockBackend;
+pub use backend::window_type::WindowType;
 pub use backend::xcb::Xcb;
 
-use core::Rectangle;
+use core::{Rectangle, SizeHints, Strut};
 use errors::*;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::os::fd::AsFd;
 
 /// A general trait for all backends (X11, XCB, Wayland)
 ///
+/// The file descriptor of a [`Backend`] becomes readable whenever
+/// there are new events, so it can be polled together with
+/// other sources like IPC sockets.
 ///
-pub trait Backend {
+/// [`Backend`]: trait.Backend.html
+pub trait Backend: AsFd {
     /// The window type to support in this [`Backend`].
     /// For example, in Xlib/xcb it would be u32.
     ///
     /// It can be any type that fullfills the requirements.
+    /// [`Command`]s refer to windows by a numeric id, so it
+    /// has to convert from and to `u32`.
+    ///
     /// [`Backend`]: trait.Backend.html
-    type Window;
+    /// [`Command`]: ../command/enum.Command.html
+    type Window: Copy + Clone + PartialEq + Eq + Debug + Hash + Serialize + From<u32> + Into<u32> +
+                 'static;
 
     /// Create a new instance of the Backend.
     ///
@@ -34,18 +58,59 @@ pub trait Backend {
     ///
     /// [`Backend`]: trait.Backend.html
     fn new() -> Result<Self> where Self: ::std::marker::Sized;
-    /// Determines if the window represented by the given ID
-    /// is a dock and should be ignored for layouts, etc.
+    /// Returns the [`Window`] the given one belongs to, e.g.
+    /// the main window of a dialog
     ///
     /// # Arguments
     /// `window` - the [`Window`] ID
     ///
     /// # Return value
     ///
-    /// True if the given [`Window`] is a dock, false otherwise
+    /// The [`Window`] the given one is transient for. It may
+    /// also be the root window or none, which stands for the
+    /// whole group of the given [`Window`].
     ///
     /// [`Window`]: trait.Backend.html#associatedtype.Window
-    fn is_dock(&self, window: Self::Window) -> bool;
+    fn transient_for(&self, window: Self::Window) -> Option<Self::Window>;
+    /// Returns the leader of the group the given [`Window`]
+    /// belongs to. All windows of an application usually
+    /// share the same leader.
+    ///
+    /// # Arguments
+    /// `window` - the [`Window`] ID
+    ///
+    /// # Return value
+    ///
+    /// The group leader, if the [`Window`] is part of a group
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn window_group(&self, window: Self::Window) -> Option<Self::Window>;
+    /// Returns the space the given [`Window`] reserves
+    /// at the edges of the screens, e.g. for a panel
+    ///
+    /// # Arguments
+    /// `window` - the [`Window`] ID
+    ///
+    /// # Return value
+    ///
+    /// The reserved [`Strut`]s, empty if the [`Window`] reserves nothing
+    ///
+    /// [`Strut`]: ../core/struct.Strut.html
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn struts(&self, window: Self::Window) -> Vec<Strut>;
+    /// Determines what the given [`Window`] is used for
+    ///
+    /// # Arguments
+    /// `window` - the [`Window`] ID
+    ///
+    /// # Return value
+    ///
+    /// The [`WindowType`], [`WindowType::Normal`] if the [`Window`] has none
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    /// [`WindowType`]: enum.WindowType.html
+    /// [`WindowType::Normal`]: enum.WindowType.html#variant.Normal
+    fn window_type(&self, window: Self::Window) -> WindowType;
     /// Determines if the given window is a real window. Real here means
     /// if it needs its own space in the layout or if it is something
     /// like a popmenu, hint, etc.
@@ -71,8 +136,8 @@ pub trait Backend {
     /// [`Rectangle`]: ../core/struct.Rectangle.html
     fn screens(&self) -> Vec<Rectangle>;
     /// Returns the number of screens. Basically just a shorthand for
-    /// ```
-    /// self.screens.len()
+    /// ```ignore
+    /// self.screens().len()
     /// ```
     ///
     /// # Return value
@@ -103,6 +168,62 @@ pub trait Backend {
     ///
     /// [`Window`]: trait.Backend.html#associatedtype.Window
     fn class_name(&self, window: Self::Window) -> Result<String>;
+    /// Determines if the given [`Window`] demands attention
+    ///
+    /// # Arguments
+    /// `window` - the [`Window`] ID
+    ///
+    /// # Return value
+    ///
+    /// `true` if the [`Window`] set its urgency hint
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn is_urgent(&self, window: Self::Window) -> bool;
+    /// Returns the sizes the given [`Window`] supports
+    ///
+    /// # Arguments
+    /// `window` - the [`Window`] ID
+    ///
+    /// # Return value
+    ///
+    /// The [`SizeHints`] of the [`Window`], empty if it has none
+    ///
+    /// [`SizeHints`]: ../core/struct.SizeHints.html
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn size_hints(&self, window: Self::Window) -> SizeHints;
+    /// Determines if the given [`Window`] is neither shown nor
+    /// hidden by a window manager, e.g. a window its client
+    /// created but never showed or withdrew again
+    ///
+    /// # Arguments
+    /// `window` - the [`Window`] ID
+    ///
+    /// # Return value
+    ///
+    /// `true` if the [`Window`] should not be managed
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn is_withdrawn(&self, window: Self::Window) -> bool;
+    /// Returns the workspace the given [`Window`] was on,
+    /// e.g. before the window manager got restarted
+    ///
+    /// # Arguments
+    /// `window` - the [`Window`] ID
+    ///
+    /// # Return value
+    ///
+    /// The id of the workspace, `None` if it is unknown
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn window_desktop(&self, window: Self::Window) -> Option<u32>;
+    /// Prepares a [`Window`] that existed before the window manager
+    /// started for being managed, as if it was just created
+    ///
+    /// # Arguments
+    /// `window` - the [`Window`] ID
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn adopt_window(&self, window: Self::Window);
     /// Returns a vector of all [`Window`] IDs currently handled
     /// by the window manager's backend.
     ///
@@ -131,7 +252,7 @@ pub trait Backend {
     /// `y` - the new y position of the upper left corner
     ///
     /// [`Window`]: trait.Backend.html#associatedtype.Window
-    fn move_window(&self, window: Self::Window, x: u32, y: u32);
+    fn move_window(&self, window: Self::Window, x: i32, y: i32);
     /// Shows/reveals the window if it has previously been hidden
     /// and notifies it about the event.
     ///
@@ -150,7 +271,44 @@ pub trait Backend {
     ///
     /// [`Window`]: trait.Backend.html#associatedtype.Window
     fn hide_window(&self, window: Self::Window);
-    /// Focusses the window, so it is ready to accept direct input
+    /// Puts the [`Window`] above all other windows
+    ///
+    /// # Arguments
+    ///
+    /// `window` - the [`Window`] ID
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn raise_window(&self, window: Self::Window);
+    /// Puts the [`Window`] below all other windows
+    ///
+    /// # Arguments
+    ///
+    /// `window` - the [`Window`] ID
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn lower_window(&self, window: Self::Window);
+    /// Asks the [`Window`] to close itself, so it can e.g. ask the
+    /// user to save changes first. Windows that don't support
+    /// this are disconnected right away.
+    ///
+    /// # Arguments
+    ///
+    /// `window` - the [`Window`] ID
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn close_window(&self, window: Self::Window);
+    /// Closes the [`Window`] without asking, killing its process
+    /// if possible. Meant for windows that don't react when
+    /// asked to close.
+    ///
+    /// # Arguments
+    ///
+    /// `window` - the [`Window`] ID
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn kill_window(&self, window: Self::Window);
+    /// Focusses the window, so it is ready to accept direct input.
+    /// Windows that don't accept input are never given the focus.
     ///
     /// # Arguments
     ///
@@ -158,6 +316,53 @@ pub trait Backend {
     ///
     /// [`Window`]: trait.Backend.html#associatedtype.Window
     fn focus_window(&self, window: Self::Window);
+    /// Grabs the given keys, so that pressing them is reported
+    /// as [`Event::KeyPressed`] regardless of the focused window.
+    /// Replaces all previously grabbed keys.
+    ///
+    /// # Arguments
+    ///
+    /// `keys` - the [`KeyCommand`]s to grab
+    ///
+    /// [`Event::KeyPressed`]: enum.Event.html#variant.KeyPressed
+    /// [`KeyCommand`]: struct.KeyCommand.html
+    fn grab_keys(&self, keys: &[KeyCommand]);
+    /// Sets the border of the given [`Window`]. The border is drawn
+    /// outside of the [`Window`]'s geometry.
+    ///
+    /// # Arguments
+    ///
+    /// `window` - the [`Window`] ID
+    /// `width` - the width of the border in pixels
+    /// `color` - the color of the border as `0xrrggbb`
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn set_window_border(&self, window: Self::Window, width: u32, color: u32);
+    /// Returns the current geometry of the given [`Window`]
+    ///
+    /// # Arguments
+    ///
+    /// `window` - the [`Window`] ID
+    ///
+    /// [`Window`]: trait.Backend.html#associatedtype.Window
+    fn window_geometry(&self, window: Self::Window) -> Result<Rectangle>;
+    /// Publishes a string for other clients, e.g. as a property of the
+    /// root window, or removes it again
+    ///
+    /// # Arguments
+    ///
+    /// `name` - the name of the property
+    /// `value` - the new value or `None` to remove the property
+    fn set_root_property(&self, name: &str, value: Option<&str>);
+    /// Publishes the workspaces and windows for other clients,
+    /// e.g. pagers and bars. Called after every change.
+    ///
+    /// # Arguments
+    ///
+    /// `desktops` - the current [`Desktops`]
+    ///
+    /// [`Desktops`]: struct.Desktops.html
+    fn publish_desktops(&self, desktops: &Desktops<Self::Window>);
     /// Blocks until an event can be provided by the backend.
     /// Does not need to be asynchronous, because as long
     /// as there is no event, the window manager does not need
@@ -169,4 +374,13 @@ pub trait Backend {
     ///
     /// [`Event`]: enum.Event.html
     fn event(&self) -> Event<Self::Window>;
+    /// Returns the next event if one is available, without blocking.
+    ///
+    /// # Return value
+    ///
+    /// An instance of the [`Event`] enum or `None` if there
+    /// are no pending events
+    ///
+    /// [`Event`]: enum.Event.html
+    fn poll_event(&self) -> Option<Event<Self::Window>>;
 }
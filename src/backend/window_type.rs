@@ -0,0 +1,76 @@
+//! The functional types of windows, as announced by the
+//! applications, e.g. in `_NET_WM_WINDOW_TYPE` on X11.
+
+/// What a window is used for. Each type comes with a default
+/// handling, which [`Rule`]s can override.
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::backend::WindowType;
+/// assert!(WindowType::Dialog.floats());
+/// assert!(!WindowType::Normal.floats());
+/// assert!(!WindowType::Notification.is_managed());
+/// ```
+///
+/// [`Rule`]: ../config/struct.Rule.html
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindowType {
+    /// A normal top level window
+    #[default]
+    Normal,
+    /// A dialog, e.g. a file chooser
+    Dialog,
+    /// A small persistent window, e.g. a palette or toolbox
+    Utility,
+    /// A splash screen shown while an application starts
+    Splash,
+    /// A toolbar torn off from the main window
+    Toolbar,
+    /// A menu torn off from the main window
+    Menu,
+    /// A menu opened from a menu bar
+    DropdownMenu,
+    /// A context menu
+    PopupMenu,
+    /// A short description shown on hovering something
+    Tooltip,
+    /// A notification bubble
+    Notification,
+    /// The popup of a combo box
+    Combo,
+    /// A window being dragged
+    Dnd,
+    /// A panel or bar, which may reserve space at the screen edges
+    Dock,
+    /// The desktop background, e.g. with icons
+    Desktop,
+}
+
+impl WindowType {
+    /// Checks if windows of this type float by default
+    ///
+    /// # Return value
+    /// `true` for dialogs, utility windows, splash screens
+    /// and torn off toolbars and menus
+    pub fn floats(&self) -> bool {
+        matches!(*self,
+                 WindowType::Dialog | WindowType::Utility | WindowType::Splash |
+                 WindowType::Toolbar | WindowType::Menu)
+    }
+
+    /// Checks if windows of this type are managed by default.
+    /// Short lived popups are left alone, docks and desktops
+    /// get a special treatment.
+    ///
+    /// # Return value
+    /// `false` for popup menus, tooltips, notifications, combo
+    /// boxes, dragged windows, docks and desktops
+    pub fn is_managed(&self) -> bool {
+        !matches!(*self,
+                  WindowType::DropdownMenu | WindowType::PopupMenu | WindowType::Tooltip |
+                  WindowType::Notification | WindowType::Combo | WindowType::Dnd |
+                  WindowType::Dock | WindowType::Desktop)
+    }
+}
@@ -0,0 +1,377 @@
+//! Publishing the window manager state through the EWMH
+//! properties, for pagers, bars and tools like `wmctrl`.
+
+use backend::{Backend, Desktops, Event, StateChange, WindowState, WindowType};
+use backend::xcb::Xcb;
+use core::{Edge, Rectangle, Strut};
+use std::mem;
+use xcb;
+
+/// The name announced on the supporting window
+const WM_NAME: &str = "sabiwm";
+
+/// All supported hints, announced in `_NET_SUPPORTED`
+const SUPPORTED: [&str; 18] = ["_NET_SUPPORTED",
+                               "_NET_SUPPORTING_WM_CHECK",
+                               "_NET_WM_NAME",
+                               "_NET_CLIENT_LIST",
+                               "_NET_CLIENT_LIST_STACKING",
+                               "_NET_ACTIVE_WINDOW",
+                               "_NET_NUMBER_OF_DESKTOPS",
+                               "_NET_CURRENT_DESKTOP",
+                               "_NET_DESKTOP_NAMES",
+                               "_NET_WORKAREA",
+                               "_NET_WM_DESKTOP",
+                               "_NET_CLOSE_WINDOW",
+                               "_NET_WM_STATE",
+                               "_NET_WM_STATE_FULLSCREEN",
+                               "_NET_WM_STATE_DEMANDS_ATTENTION",
+                               "_NET_WM_WINDOW_TYPE",
+                               "_NET_WM_STRUT",
+                               "_NET_WM_STRUT_PARTIAL"];
+
+/// The atom of each window type, also announced in `_NET_SUPPORTED`
+const WINDOW_TYPES: [(&str, WindowType); 14] =
+    [("_NET_WM_WINDOW_TYPE_NORMAL", WindowType::Normal),
+     ("_NET_WM_WINDOW_TYPE_DIALOG", WindowType::Dialog),
+     ("_NET_WM_WINDOW_TYPE_UTILITY", WindowType::Utility),
+     ("_NET_WM_WINDOW_TYPE_SPLASH", WindowType::Splash),
+     ("_NET_WM_WINDOW_TYPE_TOOLBAR", WindowType::Toolbar),
+     ("_NET_WM_WINDOW_TYPE_MENU", WindowType::Menu),
+     ("_NET_WM_WINDOW_TYPE_DROPDOWN_MENU", WindowType::DropdownMenu),
+     ("_NET_WM_WINDOW_TYPE_POPUP_MENU", WindowType::PopupMenu),
+     ("_NET_WM_WINDOW_TYPE_TOOLTIP", WindowType::Tooltip),
+     ("_NET_WM_WINDOW_TYPE_NOTIFICATION", WindowType::Notification),
+     ("_NET_WM_WINDOW_TYPE_COMBO", WindowType::Combo),
+     ("_NET_WM_WINDOW_TYPE_DND", WindowType::Dnd),
+     ("_NET_WM_WINDOW_TYPE_DOCK", WindowType::Dock),
+     ("_NET_WM_WINDOW_TYPE_DESKTOP", WindowType::Desktop)];
+
+/// `_NET_WM_DESKTOP` of windows that are on all desktops
+const ALL_DESKTOPS: u32 = 0xFFFF_FFFF;
+
+/// The atom of each supported window state
+fn state_atom(state: WindowState) -> &'static str {
+    match state {
+        WindowState::Fullscreen => "_NET_WM_STATE_FULLSCREEN",
+        WindowState::DemandsAttention => "_NET_WM_STATE_DEMANDS_ATTENTION",
+    }
+}
+
+impl Xcb {
+    /// Create the window announced in `_NET_SUPPORTING_WM_CHECK`.
+    /// Other clients know a compliant window manager is running
+    /// as long as it exists.
+    pub(super) fn create_check_window(connection: &xcb::Connection,
+                                      root: xcb::Window)
+                                      -> xcb::Window {
+        let window = connection.generate_id();
+        xcb::create_window(connection,
+                           xcb::COPY_FROM_PARENT as u8,
+                           window,
+                           root,
+                           -1,
+                           -1,
+                           1,
+                           1,
+                           0,
+                           xcb::WINDOW_CLASS_INPUT_ONLY as u16,
+                           xcb::COPY_FROM_PARENT,
+                           &[(xcb::CW_OVERRIDE_REDIRECT, 1),
+                             (xcb::CW_EVENT_MASK, xcb::EVENT_MASK_PROPERTY_CHANGE)]);
+        window
+    }
+
+    /// Announce the window manager and all supported hints
+    pub(super) fn announce(&self) {
+        debug!("announcing EWMH support on window {:?}", self.check_window);
+        for &window in &[self.root, self.check_window] {
+            self.set_property(window,
+                              "_NET_SUPPORTING_WM_CHECK",
+                              xcb::ATOM_WINDOW,
+                              &[self.check_window]);
+        }
+        self.set_utf8_property(self.check_window, "_NET_WM_NAME", WM_NAME.as_bytes());
+
+        let supported: Vec<xcb::Atom> = SUPPORTED.iter()
+            .chain(WINDOW_TYPES.iter().map(|t| &t.0))
+            .filter_map(|name| self.get_interned_atom(name).ok())
+            .collect();
+        self.set_property(self.root, "_NET_SUPPORTED", xcb::ATOM_ATOM, &supported);
+        self.connection.flush();
+    }
+
+    /// Write all properties that changed since the last call
+    pub(super) fn publish(&self, desktops: &Desktops<xcb::Window>) {
+        let old = self.published.replace(Some(desktops.clone()));
+        let old = old.as_ref();
+        let changed = |same: &dyn Fn(&Desktops<xcb::Window>) -> bool| !old.is_some_and(same);
+        let root = self.root;
+
+        if changed(&|o| o.names == desktops.names) {
+            trace!("publishing desktops {:?}", desktops.names);
+            let count = desktops.names.len() as u32;
+            let names: Vec<u8> = desktops.names
+                .iter()
+                .flat_map(|name| name.bytes().chain(Some(0)))
+                .collect();
+            self.set_property(root, "_NET_NUMBER_OF_DESKTOPS", xcb::ATOM_CARDINAL, &[count]);
+            self.set_utf8_property(root, "_NET_DESKTOP_NAMES", &names);
+        }
+
+        if changed(&|o| o.current == desktops.current) {
+            self.set_property(root,
+                              "_NET_CURRENT_DESKTOP",
+                              xcb::ATOM_CARDINAL,
+                              &[desktops.current]);
+        }
+
+        if changed(&|o| o.workarea == desktops.workarea && o.names.len() == desktops.names.len()) {
+            let area = desktops.workarea;
+            let workarea: Vec<u32> = desktops.names
+                .iter()
+                .flat_map(|_| vec![area.x() as u32, area.y() as u32, area.width(), area.height()])
+                .collect();
+            self.set_property(root, "_NET_WORKAREA", xcb::ATOM_CARDINAL, &workarea);
+        }
+
+        if changed(&|o| o.clients.iter().map(|c| c.0).eq(desktops.clients.iter().map(|c| c.0))) {
+            let clients: Vec<xcb::Window> = desktops.clients.iter().map(|c| c.0).collect();
+            self.set_property(root, "_NET_CLIENT_LIST", xcb::ATOM_WINDOW, &clients);
+        }
+
+        if changed(&|o| o.stacking == desktops.stacking) {
+            self.set_property(root,
+                              "_NET_CLIENT_LIST_STACKING",
+                              xcb::ATOM_WINDOW,
+                              &desktops.stacking);
+        }
+
+        for &(window, desktop) in &desktops.clients {
+            if !old.is_some_and(|o| o.clients.contains(&(window, desktop))) {
+                self.set_property(window, "_NET_WM_DESKTOP", xcb::ATOM_CARDINAL, &[desktop]);
+            }
+        }
+
+        for &(window, _) in &desktops.clients {
+            let states = |d: &Desktops<xcb::Window>| -> Vec<WindowState> {
+                d.states.iter().filter(|s| s.0 == window).map(|s| s.1).collect()
+            };
+            let states_now = states(desktops);
+            if old.map_or(Vec::new(), states) != states_now {
+                let atoms: Vec<xcb::Atom> = states_now.into_iter()
+                    .filter_map(|state| self.get_interned_atom(state_atom(state)).ok())
+                    .collect();
+                self.set_property(window, "_NET_WM_STATE", xcb::ATOM_ATOM, &atoms);
+            }
+        }
+
+        if changed(&|o| o.active == desktops.active) {
+            let active = desktops.active.unwrap_or(xcb::NONE);
+            self.set_property(root, "_NET_ACTIVE_WINDOW", xcb::ATOM_WINDOW, &[active]);
+        }
+
+        self.connection.flush();
+    }
+
+    /// Read `_NET_WM_DESKTOP`, unless the window is on all desktops
+    pub(super) fn read_desktop(&self, window: xcb::Window) -> Option<u32> {
+        let atom = self.get_interned_atom("_NET_WM_DESKTOP").ok()?;
+        let reply = xcb::get_property(&self.connection,
+                                      false,
+                                      window,
+                                      atom,
+                                      xcb::ATOM_CARDINAL,
+                                      0,
+                                      1)
+            .get_reply()
+            .ok()?;
+        match reply.value::<u32>().first() {
+            Some(&desktop) if reply.format() == 32 && desktop != ALL_DESKTOPS => Some(desktop),
+            _ => None,
+        }
+    }
+
+    /// Translate the requests of pagers and applications
+    pub(super) fn client_message(&self, event: &xcb::GenericEvent) -> Event<xcb::Window> {
+        let message: &xcb::ClientMessageEvent = xcb::cast_event(event);
+        let window = message.window();
+        let data = message.data().data32();
+        let is = |name: &str| self.get_interned_atom(name).ok() == Some(message.type_());
+        trace!("client message {:?} for window {:?}: {:?}",
+               message.type_(),
+               window,
+               data);
+
+        if message.format() != 32 {
+            Event::Unknown
+        } else if is("_NET_ACTIVE_WINDOW") {
+            Event::ActivateRequest(window)
+        } else if is("_NET_CLOSE_WINDOW") {
+            Event::CloseRequest(window)
+        } else if is("_NET_CURRENT_DESKTOP") {
+            Event::WorkspaceRequest(data[0])
+        } else if is("_NET_WM_DESKTOP") && data[0] != ALL_DESKTOPS {
+            Event::MoveRequest(window, data[0])
+        } else if is("_NET_WM_STATE") {
+            let change = match data[0] {
+                0 => StateChange::Remove,
+                1 => StateChange::Add,
+                2 => StateChange::Toggle,
+                _ => return Event::Unknown,
+            };
+            // Up to two states can be changed at once, but only
+            // one of them is ever supported by the window manager
+            let state = [WindowState::Fullscreen, WindowState::DemandsAttention]
+                .iter()
+                .cloned()
+                .find(|&state| {
+                    let atom = self.get_interned_atom(state_atom(state)).ok();
+                    atom == Some(data[1]) || atom == Some(data[2])
+                });
+            match state {
+                Some(state) => Event::StateRequest(window, change, state),
+                None => Event::Unknown,
+            }
+        } else {
+            Event::Unknown
+        }
+    }
+
+    /// Read `_NET_WM_WINDOW_TYPE`, which lists the types from the
+    /// most to the least specific. Transients without a type are
+    /// dialogs, all other windows without a type are normal ones.
+    pub(super) fn read_window_type(&self, window: xcb::Window) -> WindowType {
+        let atom = match self.get_interned_atom("_NET_WM_WINDOW_TYPE") {
+            Ok(atom) => atom,
+            Err(_) => return WindowType::Normal,
+        };
+        let reply = xcb::get_property(&self.connection,
+                                      false,
+                                      window,
+                                      atom,
+                                      xcb::ATOM_ATOM,
+                                      0,
+                                      u32::MAX)
+            .get_reply();
+        let types = match reply {
+            Ok(ref reply) if reply.format() == 32 => reply.value::<xcb::Atom>().to_vec(),
+            _ => Vec::new(),
+        };
+
+        let known = types.iter().filter_map(|&atom| {
+            WINDOW_TYPES.iter()
+                .find(|&&(name, _)| self.get_interned_atom(name).ok() == Some(atom))
+                .map(|&(_, window_type)| window_type)
+        });
+        match known.into_iter().next() {
+            Some(window_type) => window_type,
+            None if self.read_transient_for(window).is_some() => WindowType::Dialog,
+            None => WindowType::Normal,
+        }
+    }
+
+    /// Whether the atom is one of the strut properties
+    pub(super) fn is_strut_atom(&self, atom: xcb::Atom) -> bool {
+        ["_NET_WM_STRUT_PARTIAL", "_NET_WM_STRUT"]
+            .iter()
+            .any(|name| self.get_interned_atom(name).ok() == Some(atom))
+    }
+
+    /// Read `_NET_WM_STRUT_PARTIAL`, falling back to the older
+    /// `_NET_WM_STRUT`, and convert the reserved space into
+    /// areas in root window coordinates
+    pub(super) fn read_struts(&self, window: xcb::Window) -> Vec<Strut> {
+        let cardinals = |name: &str, length: u32| -> Option<Vec<u32>> {
+            let atom = self.get_interned_atom(name).ok()?;
+            let reply = xcb::get_property(&self.connection,
+                                          false,
+                                          window,
+                                          atom,
+                                          xcb::ATOM_CARDINAL,
+                                          0,
+                                          length)
+                .get_reply()
+                .ok()?;
+            let value = reply.value::<u32>();
+            if reply.format() == 32 && value.len() == length as usize {
+                Some(value.to_vec())
+            } else {
+                None
+            }
+        };
+        let root = match self.window_geometry(self.root) {
+            Ok(root) => root,
+            Err(err) => {
+                error!("{}", err);
+                return Vec::new();
+            }
+        };
+
+        // The old property reserves the space along the whole edge
+        let (width, height) = (root.width(), root.height());
+        let (right, bottom) = (width.saturating_sub(1), height.saturating_sub(1));
+        let values = match cardinals("_NET_WM_STRUT_PARTIAL", 12) {
+            Some(values) => values,
+            None => {
+                match cardinals("_NET_WM_STRUT", 4) {
+                    Some(v) => {
+                        vec![v[0], v[1], v[2], v[3], 0, bottom, 0, bottom, 0, right, 0, right]
+                    }
+                    None => return Vec::new(),
+                }
+            }
+        };
+
+        let span = |start: u32, end: u32| (start as i32, end.saturating_sub(start) + 1);
+        let (left_y, left_height) = span(values[4], values[5]);
+        let (right_y, right_height) = span(values[6], values[7]);
+        let (top_x, top_width) = span(values[8], values[9]);
+        let (bottom_x, bottom_width) = span(values[10], values[11]);
+        let struts = [(Edge::Left, Rectangle::new(0, left_y, values[0], left_height)),
+                      (Edge::Right,
+                       Rectangle::new(width as i32 - values[1] as i32,
+                                      right_y,
+                                      values[1],
+                                      right_height)),
+                      (Edge::Top, Rectangle::new(top_x, 0, top_width, values[2])),
+                      (Edge::Bottom,
+                       Rectangle::new(bottom_x,
+                                      height as i32 - values[3] as i32,
+                                      bottom_width,
+                                      values[3]))];
+        let struts: Vec<Strut> = struts.iter()
+            .filter(|&&(_, area)| area.width() > 0 && area.height() > 0)
+            .map(|&(edge, area)| Strut::new(edge, area))
+            .collect();
+        debug!("window {:?} reserves {:?}", window, struts);
+        struts
+    }
+
+    /// Replace the property with the given data, whose
+    /// format is derived from the size of its items
+    pub(super) fn set_property<T>(&self,
+                                  window: xcb::Window,
+                                  name: &str,
+                                  type_: xcb::Atom,
+                                  data: &[T]) {
+        let atom = match self.get_interned_atom(name) {
+            Ok(atom) => atom,
+            Err(err) => return error!("{}", err),
+        };
+        xcb::change_property(&self.connection,
+                             xcb::PROP_MODE_REPLACE as u8,
+                             window,
+                             atom,
+                             type_,
+                             (mem::size_of::<T>() * 8) as u8,
+                             data);
+    }
+
+    pub(super) fn set_utf8_property(&self, window: xcb::Window, name: &str, data: &[u8]) {
+        match self.get_interned_atom("UTF8_STRING") {
+            Ok(utf8_string) => self.set_property(window, name, utf8_string, data),
+            Err(err) => error!("{}", err),
+        }
+    }
+}
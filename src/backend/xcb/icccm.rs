@@ -0,0 +1,308 @@
+//! The parts of the ICCCM that need the window manager to
+//! talk to clients, e.g. asking a window to close itself or
+//! handing it the input focus.
+
+use backend::xcb::Xcb;
+use core::{Gravity, SizeHints};
+use nix::sys::signal::{self, Signal};
+use nix::unistd::{self, Pid};
+use xcb;
+
+/// The flags of `WM_NORMAL_HINTS` for the fields set by the program
+const P_MIN_SIZE: u32 = 1 << 4;
+const P_MAX_SIZE: u32 = 1 << 5;
+const P_RESIZE_INC: u32 = 1 << 6;
+const P_ASPECT: u32 = 1 << 7;
+const P_BASE_SIZE: u32 = 1 << 8;
+const P_WIN_GRAVITY: u32 = 1 << 9;
+
+/// The flag of `WM_HINTS` for the input field
+const INPUT_HINT: u32 = 1;
+/// The flag of `WM_HINTS` for the window group field
+const WINDOW_GROUP_HINT: u32 = 1 << 6;
+
+/// The values of `WM_STATE`
+pub(super) const WITHDRAWN_STATE: u32 = 0;
+pub(super) const NORMAL_STATE: u32 = 1;
+pub(super) const ICONIC_STATE: u32 = 3;
+
+/// The property appended to for getting a server timestamp
+const TIMESTAMP_PROPERTY: &str = "_SABIWM_TIMESTAMP";
+
+/// The number of fields of `WM_NORMAL_HINTS`
+const SIZE_HINTS_LEN: usize = 18;
+
+/// Translate the `win_gravity` field of `WM_NORMAL_HINTS`
+fn gravity(value: u32) -> Gravity {
+    match value {
+        2 => Gravity::North,
+        3 => Gravity::NorthEast,
+        4 => Gravity::West,
+        5 => Gravity::Center,
+        6 => Gravity::East,
+        7 => Gravity::SouthWest,
+        8 => Gravity::South,
+        9 => Gravity::SouthEast,
+        _ => Gravity::NorthWest,
+    }
+}
+
+impl Xcb {
+    /// Read `WM_NORMAL_HINTS`. Missing or invalid fields are left empty.
+    pub(super) fn read_size_hints(&self, window: xcb::Window) -> SizeHints {
+        let reply = xcb::get_property(&self.connection,
+                                      false,
+                                      window,
+                                      xcb::ATOM_WM_NORMAL_HINTS,
+                                      xcb::ATOM_WM_SIZE_HINTS,
+                                      0,
+                                      SIZE_HINTS_LEN as u32)
+            .get_reply();
+        let values = match reply {
+            Ok(ref reply) if reply.format() == 32 && reply.value_len() as usize >= 15 => {
+                let mut values = reply.value::<u32>().to_vec();
+                // Old clients don't set the base size and gravity
+                values.resize(SIZE_HINTS_LEN, 0);
+                values
+            }
+            _ => return SizeHints::default(),
+        };
+
+        let flags = values[0];
+        let pair = |flag: u32, index: usize| {
+            Some((values[index], values[index + 1])).filter(|_| flags & flag != 0)
+        };
+        let hints = SizeHints {
+            min_size: pair(P_MIN_SIZE, 5),
+            max_size: pair(P_MAX_SIZE, 7),
+            increment: pair(P_RESIZE_INC, 9),
+            min_aspect: pair(P_ASPECT, 11),
+            max_aspect: pair(P_ASPECT, 13),
+            base_size: pair(P_BASE_SIZE, 15),
+            gravity: if flags & P_WIN_GRAVITY != 0 {
+                gravity(values[17])
+            } else {
+                Gravity::NorthWest
+            },
+        };
+        trace!("size hints of window {:?} are {:?}", window, hints);
+        hints
+    }
+
+    /// Read `WM_STATE`, as left behind by the previous window manager
+    pub(super) fn read_wm_state(&self, window: xcb::Window) -> Option<u32> {
+        let atom = self.get_interned_atom("WM_STATE").ok()?;
+        let reply = xcb::get_property(&self.connection, false, window, atom, atom, 0, 2)
+            .get_reply()
+            .ok()?;
+        match reply.value::<u32>().first() {
+            Some(&state) if reply.format() == 32 => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Set `WM_STATE` to the given state, if it changed
+    pub(super) fn set_wm_state(&self, window: xcb::Window, state: u32) {
+        if self.wm_states.borrow_mut().insert(window, state) == Some(state) {
+            return;
+        }
+
+        trace!("setting WM_STATE of window {:?} to {}", window, state);
+        match self.get_interned_atom("WM_STATE") {
+            // The second field is the icon window, which is not used
+            Ok(atom) => self.set_property(window, "WM_STATE", atom, &[state, xcb::NONE]),
+            Err(err) => error!("{}", err),
+        }
+    }
+
+    /// Whether the window wants the window manager to set the input
+    /// focus, according to the input field of `WM_HINTS`. Windows
+    /// without the field are assumed to want it.
+    pub(super) fn accepts_input(&self, window: xcb::Window) -> bool {
+        let reply = xcb::get_property(&self.connection,
+                                      false,
+                                      window,
+                                      xcb::ATOM_WM_HINTS,
+                                      xcb::ATOM_WM_HINTS,
+                                      0,
+                                      2)
+            .get_reply();
+        match reply {
+            Ok(ref reply) if reply.format() == 32 => {
+                match *reply.value::<u32>() {
+                    [flags, input, ..] if flags & INPUT_HINT != 0 => input != 0,
+                    _ => true,
+                }
+            }
+            _ => true,
+        }
+    }
+
+    /// Read a property holding a single window
+    fn window_property(&self, window: xcb::Window, property: xcb::Atom) -> Option<xcb::Window> {
+        let reply = xcb::get_property(&self.connection,
+                                      false,
+                                      window,
+                                      property,
+                                      xcb::ATOM_WINDOW,
+                                      0,
+                                      1)
+            .get_reply()
+            .ok()?;
+        match reply.value::<xcb::Window>().first() {
+            Some(&value) if reply.format() == 32 => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Read `WM_TRANSIENT_FOR`, which may also name
+    /// the root window or no window at all
+    pub(super) fn read_transient_for(&self, window: xcb::Window) -> Option<xcb::Window> {
+        self.window_property(window, xcb::ATOM_WM_TRANSIENT_FOR)
+    }
+
+    /// Read the group leader from `WM_CLIENT_LEADER`,
+    /// falling back to the window group of `WM_HINTS`
+    pub(super) fn read_window_group(&self, window: xcb::Window) -> Option<xcb::Window> {
+        let leader = self.get_interned_atom("WM_CLIENT_LEADER")
+            .ok()
+            .and_then(|atom| self.window_property(window, atom))
+            .filter(|&leader| leader != xcb::NONE);
+        if leader.is_some() {
+            return leader;
+        }
+
+        let reply = xcb::get_property(&self.connection,
+                                      false,
+                                      window,
+                                      xcb::ATOM_WM_HINTS,
+                                      xcb::ATOM_WM_HINTS,
+                                      0,
+                                      9)
+            .get_reply()
+            .ok()?;
+        match *reply.value::<u32>() {
+            [flags, _, _, _, _, _, _, _, group, ..] if reply.format() == 32 &&
+                                                       flags & WINDOW_GROUP_HINT != 0 &&
+                                                       group != xcb::NONE => Some(group),
+            _ => None,
+        }
+    }
+
+    /// Get the current server time. Appending nothing to a property
+    /// of the check window makes the server report the time of
+    /// the change, all other events are kept for later.
+    pub(super) fn server_time(&self) -> xcb::Timestamp {
+        let atom = match self.get_interned_atom(TIMESTAMP_PROPERTY) {
+            Ok(atom) => atom,
+            Err(_) => return xcb::CURRENT_TIME,
+        };
+        xcb::change_property(&self.connection,
+                             xcb::PROP_MODE_APPEND as u8,
+                             self.check_window,
+                             atom,
+                             xcb::ATOM_CARDINAL,
+                             32,
+                             &[] as &[u32]);
+        self.connection.flush();
+
+        while let Some(event) = self.connection.wait_for_event() {
+            if event.response_type() & !0x80 == xcb::PROPERTY_NOTIFY {
+                let notify: &xcb::PropertyNotifyEvent = xcb::cast_event(&event);
+                if notify.window() == self.check_window && notify.atom() == atom {
+                    return notify.time();
+                }
+            }
+            self.pending.borrow_mut().push_back(event);
+        }
+        xcb::CURRENT_TIME
+    }
+
+    /// Whether the window lists the given protocol in `WM_PROTOCOLS`
+    pub(super) fn supports_protocol(&self, window: xcb::Window, protocol: &str) -> bool {
+        let (protocols, protocol) = match (self.get_interned_atom("WM_PROTOCOLS"),
+                                           self.get_interned_atom(protocol)) {
+            (Ok(protocols), Ok(protocol)) => (protocols, protocol),
+            _ => return false,
+        };
+        let reply = xcb::get_property(&self.connection,
+                                      false,
+                                      window,
+                                      protocols,
+                                      xcb::ATOM_ATOM,
+                                      0,
+                                      u32::MAX)
+            .get_reply();
+        match reply {
+            Ok(ref reply) if reply.format() == 32 => {
+                reply.value::<xcb::Atom>().contains(&protocol)
+            }
+            _ => false,
+        }
+    }
+
+    /// Send a `WM_PROTOCOLS` client message for the given protocol
+    pub(super) fn send_protocol(&self,
+                                window: xcb::Window,
+                                protocol: &str,
+                                time: xcb::Timestamp) {
+        let (protocols, protocol) = match (self.get_interned_atom("WM_PROTOCOLS"),
+                                           self.get_interned_atom(protocol)) {
+            (Ok(protocols), Ok(protocol)) => (protocols, protocol),
+            (Err(err), _) | (_, Err(err)) => return error!("{}", err),
+        };
+        let data = xcb::ClientMessageData::from_data32([protocol, time, 0, 0, 0]);
+        let message = xcb::ClientMessageEvent::new(32, window, protocols, data);
+        xcb::send_event(&self.connection,
+                        false,
+                        window,
+                        xcb::EVENT_MASK_NO_EVENT,
+                        &message);
+    }
+
+    /// The process of the window, if it runs on this machine.
+    /// `_NET_WM_PID` is only meaningful on the host named
+    /// in `WM_CLIENT_MACHINE`.
+    fn local_pid(&self, window: xcb::Window) -> Option<Pid> {
+        let machine = self.get_string_atom(xcb::ATOM_WM_CLIENT_MACHINE, window).ok()?;
+        let hostname = unistd::gethostname().ok()?;
+        if hostname.to_str() != Some(machine.trim_end_matches('\0')) {
+            return None;
+        }
+
+        let atom = self.get_interned_atom("_NET_WM_PID").ok()?;
+        let reply = xcb::get_property(&self.connection,
+                                      false,
+                                      window,
+                                      atom,
+                                      xcb::ATOM_CARDINAL,
+                                      0,
+                                      1)
+            .get_reply()
+            .ok()?;
+        match reply.value::<u32>().first() {
+            Some(&pid) if reply.format() == 32 && pid > 0 => Some(Pid::from_raw(pid as i32)),
+            _ => None,
+        }
+    }
+
+    /// Send `SIGKILL` to the process of the window
+    ///
+    /// # Return value
+    /// `true` if the process was found and killed
+    pub(super) fn kill_process(&self, window: xcb::Window) -> bool {
+        let pid = match self.local_pid(window) {
+            Some(pid) => pid,
+            None => return false,
+        };
+
+        debug!("killing process {} of window {:?}", pid, window);
+        match signal::kill(pid, Signal::SIGKILL) {
+            Ok(()) => true,
+            Err(err) => {
+                warn!("unable to kill process {}: {}", pid, err);
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Translating between keysyms and keycodes through the current
+//! keyboard mapping and grabbing the bound keys on the root window.
+
+use backend::keys::{KEYSYM_NUM_LOCK, KeyCommand, MOD_1, MOD_2, MOD_3, MOD_4, MOD_5, MOD_CONTROL,
+                    MOD_LOCK, MOD_SHIFT};
+use backend::xcb::Xcb;
+use xcb;
+
+/// All modifiers that are relevant for matching key bindings
+const MOD_RELEVANT: u16 = MOD_SHIFT | MOD_CONTROL | MOD_1 | MOD_2 | MOD_3 | MOD_4 | MOD_5;
+
+/// A snapshot of the server's keyboard and modifier mapping
+/// together with the keys currently grabbed.
+#[derive(Default)]
+pub(super) struct Keyboard {
+    min_keycode: xcb::Keycode,
+    keysyms_per_keycode: usize,
+    keysyms: Vec<xcb::Keysym>,
+    num_lock: u16,
+    grabbed: Vec<KeyCommand>,
+}
+
+impl Keyboard {
+    /// Query the current keyboard and modifier mapping from the server
+    pub(super) fn query(connection: &xcb::Connection) -> Keyboard {
+        let (min_keycode, max_keycode) = {
+            let setup = connection.get_setup();
+            (setup.min_keycode(), setup.max_keycode())
+        };
+        let count = max_keycode - min_keycode + 1;
+
+        let mut keyboard = Keyboard { min_keycode, ..Keyboard::default() };
+        match xcb::get_keyboard_mapping(connection, min_keycode, count).get_reply() {
+            Ok(reply) => {
+                keyboard.keysyms_per_keycode = reply.keysyms_per_keycode() as usize;
+                keyboard.keysyms = reply.keysyms().to_vec();
+            }
+            Err(err) => error!("unable to get keyboard mapping: {:?}", err),
+        }
+
+        keyboard.num_lock = keyboard.query_num_lock(connection);
+        debug!("num lock modifier is {:#x}", keyboard.num_lock);
+        keyboard
+    }
+
+    /// Find the modifier mask num lock is mapped to
+    fn query_num_lock(&self, connection: &xcb::Connection) -> u16 {
+        let num_lock = self.keycodes(KEYSYM_NUM_LOCK);
+        let reply = match xcb::get_modifier_mapping(connection).get_reply() {
+            Ok(reply) => reply,
+            Err(err) => {
+                error!("unable to get modifier mapping: {:?}", err);
+                return 0;
+            }
+        };
+
+        let per_modifier = reply.keycodes_per_modifier() as usize;
+        reply.keycodes()
+            .iter()
+            .position(|keycode| num_lock.contains(keycode))
+            .map_or(0, |index| 1 << (index / per_modifier.max(1)))
+    }
+
+    /// All keycodes producing the given keysym without any modifier
+    fn keycodes(&self, keysym: xcb::Keysym) -> Vec<xcb::Keycode> {
+        if self.keysyms_per_keycode == 0 {
+            return Vec::new();
+        }
+
+        self.keysyms
+            .chunks(self.keysyms_per_keycode)
+            .enumerate()
+            .filter(|&(_, syms)| syms[0] == keysym)
+            .map(|(index, _)| self.min_keycode + index as xcb::Keycode)
+            .collect()
+    }
+
+    /// The keysym the given keycode produces without any modifier
+    fn keysym(&self, keycode: xcb::Keycode) -> xcb::Keysym {
+        let index = keycode.saturating_sub(self.min_keycode) as usize * self.keysyms_per_keycode;
+        self.keysyms.get(index).cloned().unwrap_or(0)
+    }
+
+    /// All combinations of caps lock and num lock, which
+    /// should not make a difference for key bindings.
+    fn lock_variants(&self) -> [u16; 4] {
+        [0, MOD_LOCK, self.num_lock, MOD_LOCK | self.num_lock]
+    }
+
+    /// Translate a key press into a [`KeyCommand`], ignoring lock modifiers
+    ///
+    /// [`KeyCommand`]: ../../keys/struct.KeyCommand.html
+    fn key_command(&self, keycode: xcb::Keycode, state: u16) -> KeyCommand {
+        let modifiers = state & MOD_RELEVANT & !self.num_lock;
+        KeyCommand::new(self.keysym(keycode), modifiers)
+    }
+}
+
+impl Xcb {
+    /// Grab all given keys on the root window, including their
+    /// caps lock and num lock variants. All previous grabs are released.
+    pub(super) fn grab(&self, keys: &[KeyCommand]) {
+        let mut keyboard = self.keyboard.borrow_mut();
+        xcb::ungrab_key(&self.connection,
+                        xcb::GRAB_ANY as xcb::Keycode,
+                        self.root,
+                        xcb::MOD_MASK_ANY as u16);
+
+        for key in keys {
+            let keycodes = keyboard.keycodes(key.keysym);
+            if keycodes.is_empty() {
+                warn!("no keycode for key {}, not grabbing it", key);
+            }
+
+            for keycode in keycodes {
+                for variant in &keyboard.lock_variants() {
+                    trace!("grabbing keycode {} with modifiers {:#x}",
+                           keycode,
+                           key.modifiers | variant);
+                    xcb::grab_key(&self.connection,
+                                  true,
+                                  self.root,
+                                  key.modifiers | variant,
+                                  keycode,
+                                  xcb::GRAB_MODE_ASYNC as u8,
+                                  xcb::GRAB_MODE_ASYNC as u8);
+                }
+            }
+        }
+
+        keyboard.grabbed = keys.to_vec();
+        self.connection.flush();
+    }
+
+    /// Translate a key press event into the pressed key and the window
+    /// it was pressed in, or the root window if there is none.
+    pub(super) fn key_press(&self, event: &xcb::GenericEvent) -> (xcb::Window, KeyCommand) {
+        let key_press: &xcb::KeyPressEvent = xcb::cast_event(event);
+        let key = self.keyboard.borrow().key_command(key_press.detail(), key_press.state());
+        debug!("key {} pressed", key);
+        let window = match key_press.child() {
+            xcb::NONE => key_press.event(),
+            child => child,
+        };
+        (window, key)
+    }
+
+    /// Reload the keyboard mapping after it changed and
+    /// grab the bound keys again with their new keycodes.
+    pub(super) fn mapping_changed(&self, event: &xcb::GenericEvent) {
+        let mapping_notify: &xcb::MappingNotifyEvent = xcb::cast_event(event);
+        if mapping_notify.request() == xcb::MAPPING_POINTER as u8 {
+            return;
+        }
+
+        debug!("keyboard mapping changed, grabbing keys again");
+        let grabbed = {
+            let mut keyboard = self.keyboard.borrow_mut();
+            let grabbed = keyboard.grabbed.clone();
+            *keyboard = Keyboard::query(&self.connection);
+            grabbed
+        };
+        self.grab(&grabbed);
+    }
+}
@@ -1,20 +1,51 @@
-use backend::{Backend, Event};
-use core::Rectangle;
+mod ewmh;
+mod icccm;
+mod keyboard;
+mod screens;
+
+use backend::{Backend, Desktops, Event, KeyCommand, WindowType};
+use backend::xcb::icccm::{ICONIC_STATE, NORMAL_STATE, WITHDRAWN_STATE};
+use backend::xcb::keyboard::Keyboard;
+use core::{Rectangle, SizeHints, Strut};
 use errors::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::os::fd::{AsFd, BorrowedFd};
 use xcb;
 
+/// The urgency flag of the WM_HINTS property
+const URGENCY_HINT: u32 = 1 << 8;
+
+/// The events the window manager needs from managed windows. Their
+/// title and hints changing and the mouse pointer entering them.
+const CLIENT_EVENT_MASK: u32 = xcb::EVENT_MASK_PROPERTY_CHANGE | xcb::EVENT_MASK_ENTER_WINDOW;
+
 /// The Xcb backend. This backend shall be the default,
 /// until Wayland becomes the default environment.
 pub struct Xcb {
     connection: xcb::Connection,
     root: xcb::Window,
+    randr_base: Option<u8>,
+    keyboard: RefCell<Keyboard>,
+    atoms: RefCell<HashMap<String, xcb::Atom>>,
+    check_window: xcb::Window,
+    published: RefCell<Option<Desktops<xcb::Window>>>,
+    pending: RefCell<VecDeque<xcb::GenericEvent>>,
+    wm_states: RefCell<HashMap<xcb::Window, u32>>,
+    /// The number of unmaps caused by the window manager
+    /// itself that were not reported yet, per window
+    expected_unmaps: RefCell<HashMap<xcb::Window, u32>>,
 }
 
 impl Xcb {
     fn create_window(&self, event: &xcb::GenericEvent) -> Event<xcb::Window> {
         let map_request: &xcb::MapRequestEvent = xcb::cast_event(event);
         debug!("xcb map request for new window {:?}", map_request.window());
+        xcb::change_window_attributes(&self.connection,
+                                      map_request.window(),
+                                      &[(xcb::CW_EVENT_MASK, CLIENT_EVENT_MASK)]);
         xcb::map_window(&self.connection, map_request.window());
+        self.set_wm_state(map_request.window(), NORMAL_STATE);
         self.connection.flush();
         Event::WindowCreated(map_request.window())
     }
@@ -23,10 +54,181 @@ impl Xcb {
         let destroy_notify: &xcb::DestroyNotifyEvent = xcb::cast_event(event);
         debug!("xcb destroy notification for window {:?}",
                destroy_notify.window());
+        self.wm_states.borrow_mut().remove(&destroy_notify.window());
+        self.expected_unmaps.borrow_mut().remove(&destroy_notify.window());
         self.connection.flush();
         Event::WindowClosed(destroy_notify.window())
     }
 
+    /// Tell unmaps of the window manager from clients withdrawing
+    /// their windows. Clients withdrawing a window that is already
+    /// unmapped send a synthetic notification instead.
+    fn window_unmapped(&self, event: &xcb::GenericEvent) -> Event<xcb::Window> {
+        let unmap_notify: &xcb::UnmapNotifyEvent = xcb::cast_event(event);
+        let window = unmap_notify.window();
+        let synthetic = event.response_type() & 0x80 != 0;
+
+        let expected = {
+            let mut expected_unmaps = self.expected_unmaps.borrow_mut();
+            match expected_unmaps.get_mut(&window) {
+                Some(count) if !synthetic && *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false,
+            }
+        };
+        if expected {
+            trace!("window {:?} got hidden", window);
+            return Event::WindowHid(window);
+        }
+
+        debug!("window {:?} got withdrawn", window);
+        self.expected_unmaps.borrow_mut().remove(&window);
+        // Only windows the window manager gave a state need it
+        // withdrawn, e.g. not override redirect popups
+        if self.wm_states.borrow().contains_key(&window) {
+            self.set_wm_state(window, WITHDRAWN_STATE);
+            self.wm_states.borrow_mut().remove(&window);
+            self.connection.flush();
+        }
+        Event::WindowClosed(window)
+    }
+
+    fn window_mapped(&self, event: &xcb::GenericEvent) -> Event<xcb::Window> {
+        let map_notify: &xcb::MapNotifyEvent = xcb::cast_event(event);
+        trace!("window {:?} got revealed", map_notify.window());
+        Event::WindowRevealed(map_notify.window())
+    }
+
+    /// Clients ask to move or resize their windows with configure
+    /// requests. Whatever the client left out stays as it is.
+    fn configure_request(&self, event: &xcb::GenericEvent) -> Event<xcb::Window> {
+        let configure_request: &xcb::ConfigureRequestEvent = xcb::cast_event(event);
+        let window = configure_request.window();
+        let mask = configure_request.value_mask();
+        let requested = |flag: xcb::ConfigWindow| mask & flag as u16 != 0;
+        let current = match self.window_geometry(window) {
+            Ok(geometry) => geometry,
+            Err(err) => {
+                debug!("ignoring configure request: {}", err);
+                return Event::Unknown;
+            }
+        };
+
+        let x = if requested(xcb::CONFIG_WINDOW_X) {
+            configure_request.x() as i32
+        } else {
+            current.x()
+        };
+        let y = if requested(xcb::CONFIG_WINDOW_Y) {
+            configure_request.y() as i32
+        } else {
+            current.y()
+        };
+        let width = if requested(xcb::CONFIG_WINDOW_WIDTH) {
+            configure_request.width() as u32
+        } else {
+            current.width()
+        };
+        let height = if requested(xcb::CONFIG_WINDOW_HEIGHT) {
+            configure_request.height() as u32
+        } else {
+            current.height()
+        };
+        let rectangle = Rectangle::new(x, y, width, height);
+        trace!("window {:?} asks for {:?}", window, rectangle);
+        Event::WindowChangeRequest(window, rectangle)
+    }
+
+    /// Only crossings of the pointer itself count, not the ones
+    /// caused by grabs or by moving between a window and its children
+    fn mouse_entered(&self, event: &xcb::GenericEvent) -> Event<xcb::Window> {
+        let enter_notify: &xcb::EnterNotifyEvent = xcb::cast_event(event);
+        if enter_notify.mode() != xcb::NOTIFY_MODE_NORMAL as u8 ||
+           enter_notify.detail() == xcb::NOTIFY_DETAIL_INFERIOR as u8 {
+            return Event::Unknown;
+        }
+        trace!("mouse entered window {:?}", enter_notify.event());
+        Event::MouseEnter(Some(enter_notify.event()))
+    }
+
+    /// Clicks are grabbed on the root window, so the click is
+    /// passed on to the window below the pointer afterwards.
+    fn button_pressed(&self, event: &xcb::GenericEvent) -> Event<xcb::Window> {
+        let button_press: &xcb::ButtonPressEvent = xcb::cast_event(event);
+        xcb::allow_events(&self.connection,
+                          xcb::ALLOW_REPLAY_POINTER as u8,
+                          button_press.time());
+        self.connection.flush();
+        let child = match button_press.child() {
+            xcb::NONE => None,
+            child => Some(child),
+        };
+        trace!("button {} pressed on window {:?}",
+               button_press.detail(),
+               child);
+        Event::ButtonPressed(button_press.event(), child)
+    }
+
+    fn is_mapped(&self, window: xcb::Window) -> bool {
+        xcb::get_window_attributes(&self.connection, window)
+            .get_reply()
+            .map(|reply| reply.map_state() != xcb::MAP_STATE_UNMAPPED as u8)
+            .unwrap_or(false)
+    }
+
+    fn property_changed(&self, event: &xcb::GenericEvent) -> Event<xcb::Window> {
+        let property_notify: &xcb::PropertyNotifyEvent = xcb::cast_event(event);
+        let atom = property_notify.atom();
+        let is_title = atom == xcb::ATOM_WM_NAME ||
+                       self.get_interned_atom("_NET_WM_NAME").ok() == Some(atom);
+        if is_title {
+            trace!("title of window {:?} changed", property_notify.window());
+            Event::WindowTitleChanged(property_notify.window())
+        } else if atom == xcb::ATOM_WM_HINTS || atom == xcb::ATOM_WM_NORMAL_HINTS {
+            trace!("hints of window {:?} changed", property_notify.window());
+            Event::WindowHintsChanged(property_notify.window())
+        } else if self.is_strut_atom(atom) {
+            trace!("struts of window {:?} changed", property_notify.window());
+            Event::StrutsChanged(property_notify.window())
+        } else {
+            Event::Unknown
+        }
+    }
+
+    fn translate_event(&self, event: &xcb::GenericEvent) -> Event<xcb::Window> {
+        debug!("received event");
+        let response_type = event.response_type() & !0x80;
+        match response_type {
+            xcb::MAP_REQUEST => self.create_window(event),
+            xcb::DESTROY_NOTIFY => self.destroy_window(event),
+            xcb::UNMAP_NOTIFY => self.window_unmapped(event),
+            xcb::MAP_NOTIFY => self.window_mapped(event),
+            xcb::CONFIGURE_REQUEST => self.configure_request(event),
+            xcb::PROPERTY_NOTIFY => self.property_changed(event),
+            xcb::CLIENT_MESSAGE => self.client_message(event),
+            xcb::ENTER_NOTIFY => self.mouse_entered(event),
+            xcb::BUTTON_PRESS => self.button_pressed(event),
+            xcb::KEY_PRESS => {
+                let (window, key) = self.key_press(event);
+                Event::KeyPressed(window, key)
+            }
+            xcb::MAPPING_NOTIFY => {
+                self.mapping_changed(event);
+                Event::Unknown
+            }
+            _ if self.is_randr_change(response_type) => {
+                debug!("screen layout changed");
+                Event::BackendChanged
+            }
+            _ => {
+                warn!("unknown request {:?}", response_type);
+                Event::Unknown
+            }
+        }
+    }
+
     fn set_event_mask(connection: &xcb::Connection, root: xcb::Window) {
         debug!("setting root window properties");
         let values =
@@ -43,6 +245,22 @@ impl Xcb {
         }
     }
 
+    /// Grab all buttons on the root window, so clicks into
+    /// any window can focus it before they are passed on.
+    fn grab_buttons(connection: &xcb::Connection, root: xcb::Window) {
+        debug!("grabbing buttons");
+        xcb::grab_button(connection,
+                         false,
+                         root,
+                         xcb::EVENT_MASK_BUTTON_PRESS as u16,
+                         xcb::GRAB_MODE_SYNC as u8,
+                         xcb::GRAB_MODE_ASYNC as u8,
+                         xcb::NONE,
+                         xcb::NONE,
+                         xcb::BUTTON_INDEX_ANY as u8,
+                         xcb::MOD_MASK_ANY as u16);
+    }
+
     fn acquire_root_window(connection: &xcb::Connection, screen_number: i32) -> xcb::Window {
         let setup = connection.get_setup();
         let screen = setup.roots().nth(screen_number as usize).unwrap();
@@ -53,10 +271,15 @@ impl Xcb {
     }
 
     fn get_interned_atom(&self, atom: &str) -> Result<xcb::Atom> {
-        Ok(xcb::intern_atom(&self.connection, false, atom)
+        if let Some(&interned) = self.atoms.borrow().get(atom) {
+            return Ok(interned);
+        }
+        let interned = xcb::intern_atom(&self.connection, false, atom)
             .get_reply()
             .map_err(|_| format!("unable to get atom {}", atom))?
-            .atom())
+            .atom();
+        self.atoms.borrow_mut().insert(String::from(atom), interned);
+        Ok(interned)
     }
 
     fn get_string_atom(&self, atom: xcb::Atom, window: xcb::Window) -> Result<String> {
@@ -66,10 +289,10 @@ impl Xcb {
                                       atom,
                                       xcb::ATOM_STRING,
                                       0,
-                                      u32::max_value()).get_reply()
+                                      u32::MAX).get_reply()
             .map_err(|err| format!("{:?}", err))?;
         match String::from_utf8(reply.value().to_vec()) {
-            Ok(ref name) if name.len() > 0 => Ok(name.clone()),
+            Ok(ref name) if !name.is_empty() => Ok(name.clone()),
             _ => bail!("unable to get property"),
         }
     }
@@ -86,30 +309,47 @@ impl Backend for Xcb {
         let root = Xcb::acquire_root_window(&conn, screen_number);
         debug!("acquired root window {:?}", root);
         Xcb::set_event_mask(&conn, root);
+        Xcb::grab_buttons(&conn, root);
+        let randr_base = Xcb::initialize_randr(&conn, root);
+        let keyboard = RefCell::new(Keyboard::query(&conn));
+        let check_window = Xcb::create_check_window(&conn, root);
         conn.flush();
 
-        Ok(Xcb {
+        let xcb = Xcb {
             connection: conn,
-            root: root,
-        })
-    }
-
-    fn is_dock(&self, window: Self::Window) -> bool {
-        trace!("checking if {:?} is a dock", window);
-        let dock = try_or_false!(self.get_interned_atom("_NET_WM_WINDOW_TYPE_DOCK"));
-        let desk = try_or_false!(self.get_interned_atom("_NET_WM_WINDOW_TYPE_DESKTOP"));
-        let window_type = try_or_false!(self.get_interned_atom("_NET_WM_WINDOW_TYPE"));
-
-        xcb::get_property(&self.connection,
-                          false,
-                          window,
-                          window_type,
-                          xcb::ATOM_ATOM,
-                          0,
-                          u32::max_value())
-            .get_reply()
-            .iter()
-            .any(|x| x.type_() == dock || x.type_() == desk)
+            root,
+            randr_base,
+            keyboard,
+            atoms: RefCell::new(HashMap::new()),
+            check_window,
+            published: RefCell::new(None),
+            pending: RefCell::new(VecDeque::new()),
+            wm_states: RefCell::new(HashMap::new()),
+            expected_unmaps: RefCell::new(HashMap::new()),
+        };
+        xcb.announce();
+        Ok(xcb)
+    }
+
+    fn window_type(&self, window: Self::Window) -> WindowType {
+        self.read_window_type(window)
+    }
+
+    fn size_hints(&self, window: Self::Window) -> SizeHints {
+        self.read_size_hints(window)
+    }
+
+    fn transient_for(&self, window: Self::Window) -> Option<Self::Window> {
+        self.read_transient_for(window)
+    }
+
+    fn window_group(&self, window: Self::Window) -> Option<Self::Window> {
+        self.read_window_group(window)
+    }
+
+    fn struts(&self, window: Self::Window) -> Vec<Strut> {
+        trace!("retrieving struts of window {:?}", window);
+        self.read_struts(window)
     }
 
     fn is_window(&self, window: Self::Window) -> bool {
@@ -121,11 +361,13 @@ impl Backend for Xcb {
 
     fn screens(&self) -> Vec<Rectangle> {
         trace!("getting screen layout information");
-        unimplemented!();
+        let screens = self.query_screens();
+        debug!("found screens {:?}", screens);
+        screens
     }
 
     fn number_of_screens(&self) -> usize {
-        self.connection.get_setup().roots_len() as usize
+        self.screens().len()
     }
 
     fn window_name(&self, window: Self::Window) -> Result<String> {
@@ -139,7 +381,54 @@ impl Backend for Xcb {
 
     fn class_name(&self, window: Self::Window) -> Result<String> {
         trace!("retrieving class name of window {:?}", window);
-        self.get_string_atom(xcb::ATOM_WM_CLASS, window)
+        // WM_CLASS holds the instance and the class name,
+        // each terminated by a null byte.
+        let class = self.get_string_atom(xcb::ATOM_WM_CLASS, window)?;
+        match class.split('\0').rfind(|s| !s.is_empty()) {
+            Some(name) => Ok(String::from(name)),
+            None => bail!("window {:?} has no class", window),
+        }
+    }
+
+    fn is_urgent(&self, window: Self::Window) -> bool {
+        let reply = xcb::get_property(&self.connection,
+                                      false,
+                                      window,
+                                      xcb::ATOM_WM_HINTS,
+                                      xcb::ATOM_WM_HINTS,
+                                      0,
+                                      1)
+            .get_reply();
+        // The first field of WM_HINTS holds the flags
+        match reply {
+            Ok(ref reply) if reply.format() == 32 => {
+                reply.value::<u32>().first().is_some_and(|flags| flags & URGENCY_HINT != 0)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_withdrawn(&self, window: Self::Window) -> bool {
+        let state = self.read_wm_state(window);
+        !self.is_mapped(window) && state != Some(NORMAL_STATE) && state != Some(ICONIC_STATE)
+    }
+
+    fn window_desktop(&self, window: Self::Window) -> Option<u32> {
+        self.read_desktop(window)
+    }
+
+    fn adopt_window(&self, window: Self::Window) {
+        debug!("adopting window {:?}", window);
+        xcb::change_window_attributes(&self.connection,
+                                      window,
+                                      &[(xcb::CW_EVENT_MASK, CLIENT_EVENT_MASK)]);
+        let state = if self.is_mapped(window) {
+            NORMAL_STATE
+        } else {
+            ICONIC_STATE
+        };
+        self.set_wm_state(window, state);
+        self.connection.flush();
     }
 
     fn windows(&self) -> Result<Vec<Self::Window>> {
@@ -147,9 +436,7 @@ impl Backend for Xcb {
             .get_reply()
             .map_err(|_| "unable to query xcb tree")?
             .children()
-            .iter()
-            .cloned()
-            .collect())
+            .to_vec())
     }
 
     fn resize_window(&self, window: Self::Window, width: u32, height: u32) {
@@ -160,43 +447,161 @@ impl Backend for Xcb {
         self.connection.flush();
     }
 
-    fn move_window(&self, window: Self::Window, x: u32, y: u32) {
+    fn move_window(&self, window: Self::Window, x: i32, y: i32) {
         trace!("moving window {:?} to {}x{}", window, x, y);
-        let values = [(xcb::CONFIG_WINDOW_X as u16, x), (xcb::CONFIG_WINDOW_Y as u16, y)];
+        // The server reads the values as signed, so negative
+        // positions survive the conversion unchanged
+        let values = [(xcb::CONFIG_WINDOW_X as u16, x as u32),
+                      (xcb::CONFIG_WINDOW_Y as u16, y as u32)];
         xcb::configure_window(&self.connection, window, &values);
         self.connection.flush();
     }
 
     fn show_window(&self, window: Self::Window) {
+        trace!("showing window {:?}", window);
         xcb::map_window(&self.connection, window);
+        self.set_wm_state(window, NORMAL_STATE);
+        self.connection.flush();
     }
 
     fn hide_window(&self, window: Self::Window) {
-        xcb::unmap_window(&self.connection, window);
+        if self.wm_states.borrow().get(&window) == Some(&ICONIC_STATE) {
+            return;
+        }
+
+        // Only unmapping a mapped window causes a notification
+        let mapped = self.is_mapped(window);
+        trace!("hiding window {:?}, mapped: {}", window, mapped);
+        if mapped {
+            *self.expected_unmaps.borrow_mut().entry(window).or_insert(0) += 1;
+            xcb::unmap_window(&self.connection, window);
+        }
+        self.set_wm_state(window, ICONIC_STATE);
+        self.connection.flush();
+    }
+
+    fn raise_window(&self, window: Self::Window) {
+        trace!("raising window {:?}", window);
+        let values = [(xcb::CONFIG_WINDOW_STACK_MODE as u16, xcb::STACK_MODE_ABOVE)];
+        xcb::configure_window(&self.connection, window, &values);
+        self.connection.flush();
+    }
+
+    fn lower_window(&self, window: Self::Window) {
+        trace!("lowering window {:?}", window);
+        let values = [(xcb::CONFIG_WINDOW_STACK_MODE as u16, xcb::STACK_MODE_BELOW)];
+        xcb::configure_window(&self.connection, window, &values);
+        self.connection.flush();
+    }
+
+    fn close_window(&self, window: Self::Window) {
+        if self.supports_protocol(window, "WM_DELETE_WINDOW") {
+            debug!("asking window {:?} to close", window);
+            self.send_protocol(window, "WM_DELETE_WINDOW", xcb::TIME_CURRENT_TIME);
+        } else {
+            debug!("disconnecting window {:?}", window);
+            xcb::kill_client(&self.connection, window);
+        }
+        self.connection.flush();
+    }
+
+    fn kill_window(&self, window: Self::Window) {
+        if !self.kill_process(window) {
+            debug!("no local process for window {:?}", window);
+        }
+        // Also takes care of clients on other machines
+        xcb::kill_client(&self.connection, window);
+        self.connection.flush();
     }
 
     fn focus_window(&self, window: Self::Window) {
-        xcb::set_input_focus(&self.connection, 0, window, xcb::CURRENT_TIME);
+        // The four input models of the ICCCM: no input, passive,
+        // locally active and globally active. Only windows that take
+        // input get the focus, the others decide on their own.
+        let input = self.accepts_input(window);
+        let take_focus = self.supports_protocol(window, "WM_TAKE_FOCUS");
+        trace!("focusing window {:?}, input: {}, take focus: {}",
+               window,
+               input,
+               take_focus);
+        if input {
+            xcb::set_input_focus(&self.connection,
+                                 xcb::INPUT_FOCUS_POINTER_ROOT as u8,
+                                 window,
+                                 xcb::CURRENT_TIME);
+        }
+        if take_focus {
+            let time = self.server_time();
+            self.send_protocol(window, "WM_TAKE_FOCUS", time);
+        }
+        self.connection.flush();
     }
 
-    fn event(&self) -> Event<Self::Window> {
-        trace!("waiting for next event");
-        let event = self.connection.wait_for_event();
-
-        match event {
-            Some(event) => {
-                debug!("received event");
-                let response_type = event.response_type();
-                match response_type {
-                    xcb::MAP_REQUEST => self.create_window(&event),
-                    xcb::DESTROY_NOTIFY => self.destroy_window(&event),
-                    _ => {
-                        warn!("unknown request {:?}", response_type);
-                        Event::Unknown
-                    }
+    fn grab_keys(&self, keys: &[KeyCommand]) {
+        debug!("grabbing {} keys", keys.len());
+        self.grab(keys);
+    }
+
+    fn set_window_border(&self, window: Self::Window, width: u32, color: u32) {
+        trace!("setting border of window {:?} to {} pixels of {:#08x}",
+               window,
+               width,
+               color);
+        xcb::configure_window(&self.connection,
+                              window,
+                              &[(xcb::CONFIG_WINDOW_BORDER_WIDTH as u16, width)]);
+        xcb::change_window_attributes(&self.connection, window, &[(xcb::CW_BORDER_PIXEL, color)]);
+        self.connection.flush();
+    }
+
+    fn window_geometry(&self, window: Self::Window) -> Result<Rectangle> {
+        let geometry = xcb::get_geometry(&self.connection, window).get_reply()
+            .map_err(|_| format!("unable to get geometry of window {:?}", window))?;
+        Ok(Rectangle::new(geometry.x() as i32,
+                          geometry.y() as i32,
+                          geometry.width() as u32,
+                          geometry.height() as u32))
+    }
+
+    fn set_root_property(&self, name: &str, value: Option<&str>) {
+        trace!("setting root property {} to {:?}", name, value);
+        match value {
+            Some(value) => self.set_utf8_property(self.root, name, value.as_bytes()),
+            None => {
+                if let Ok(atom) = self.get_interned_atom(name) {
+                    xcb::delete_property(&self.connection, self.root, atom);
                 }
             }
-            _ => Event::Unknown,
+        }
+        self.connection.flush();
+    }
+
+    fn publish_desktops(&self, desktops: &Desktops<Self::Window>) {
+        self.publish(desktops);
+    }
+
+    fn event(&self) -> Event<Self::Window> {
+        trace!("waiting for next event");
+        let event = self.pending.borrow_mut().pop_front();
+        match event.or_else(|| self.connection.wait_for_event()) {
+            Some(event) => self.translate_event(&event),
+            None => Event::Unknown,
+        }
+    }
+
+    fn poll_event(&self) -> Option<Event<Self::Window>> {
+        let event = self.pending.borrow_mut().pop_front();
+        event.or_else(|| self.connection.poll_for_event()).map(|event| self.translate_event(&event))
+    }
+}
+
+impl AsFd for Xcb {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // The descriptor stays open as long as the connection,
+        // which lives as long as `self`.
+        unsafe {
+            BorrowedFd::borrow_raw(xcb::ffi::base::xcb_get_file_descriptor(self.connection
+                .get_raw_conn()))
         }
     }
 }
@@ -0,0 +1,153 @@
+//! Querying the monitor layout through RandR, with Xinerama
+//! and finally the plain X screen as fallbacks.
+
+use backend::xcb::Xcb;
+use core::Rectangle;
+use xcb;
+use xcb::{randr, xinerama};
+
+/// Remove all screens that are identical to or contained in another
+/// screen, e.g. a cloned output showing a part of a bigger one.
+fn remove_clones(screens: Vec<Rectangle>) -> Vec<Rectangle> {
+    let mut result: Vec<Rectangle> = Vec::new();
+    for (i, screen) in screens.iter().enumerate() {
+        let duplicate = result.contains(screen);
+        let contained = screens.iter()
+            .enumerate()
+            .any(|(j, other)| i != j && other != screen && other.contains(screen));
+        if !duplicate && !contained {
+            result.push(*screen);
+        }
+    }
+    result
+}
+
+impl Xcb {
+    /// Query the extension data of RandR and subscribe to
+    /// screen change notifications if RandR is available.
+    ///
+    /// # Return value
+    /// The first event number of RandR, if the extension is present
+    pub(super) fn initialize_randr(connection: &xcb::Connection, root: xcb::Window) -> Option<u8> {
+        let first_event = match connection.get_extension_data(randr::id()) {
+            Some(ref data) if data.present() => data.first_event(),
+            _ => {
+                info!("randr extension not available");
+                return None;
+            }
+        };
+
+        match randr::query_version(connection, 1, 2).get_reply() {
+            Ok(ref version) if (version.major_version(), version.minor_version()) >= (1, 2) => {
+                debug!("randr version {}.{}",
+                       version.major_version(),
+                       version.minor_version())
+            }
+            _ => {
+                info!("randr 1.2 not supported");
+                return None;
+            }
+        }
+
+        let mask = randr::NOTIFY_MASK_SCREEN_CHANGE | randr::NOTIFY_MASK_CRTC_CHANGE |
+                   randr::NOTIFY_MASK_OUTPUT_CHANGE;
+        randr::select_input(connection, root, mask as u16);
+        Some(first_event)
+    }
+
+    /// Checks if the given response type is one of the
+    /// RandR notifications signalling a change of the monitor layout
+    pub(super) fn is_randr_change(&self, response_type: u8) -> bool {
+        self.randr_base.is_some_and(|base| {
+            response_type == base + randr::SCREEN_CHANGE_NOTIFY ||
+            response_type == base + randr::NOTIFY
+        })
+    }
+
+    /// Get the geometry of all active CRTCs driving a connected output
+    fn randr_screens(&self) -> Option<Vec<Rectangle>> {
+        self.randr_base?;
+
+        let resources = randr::get_screen_resources_current(&self.connection, self.root)
+            .get_reply()
+            .ok()?;
+        let timestamp = resources.config_timestamp();
+
+        let screens: Vec<Rectangle> = resources.crtcs()
+            .iter()
+            .filter_map(|&crtc| {
+                randr::get_crtc_info(&self.connection, crtc, timestamp).get_reply().ok()
+            })
+            .filter(|info| info.width() > 0 && info.height() > 0)
+            .filter(|info| {
+                info.outputs().iter().any(|&output| {
+                    randr::get_output_info(&self.connection, output, timestamp)
+                        .get_reply()
+                        .map(|o| o.connection() == randr::CONNECTION_CONNECTED as u8)
+                        .unwrap_or(false)
+                })
+            })
+            .map(|info| {
+                Rectangle::new(info.x() as i32,
+                               info.y() as i32,
+                               info.width() as u32,
+                               info.height() as u32)
+            })
+            .collect();
+
+        if screens.is_empty() { None } else { Some(screens) }
+    }
+
+    /// Get the geometry of all Xinerama screens
+    fn xinerama_screens(&self) -> Option<Vec<Rectangle>> {
+        let active = xinerama::is_active(&self.connection).get_reply().ok()?;
+        if active.state() == 0 {
+            return None;
+        }
+
+        let screens: Vec<Rectangle> = xinerama::query_screens(&self.connection)
+            .get_reply()
+            .ok()?
+            .screen_info()
+            .map(|info| {
+                Rectangle::new(info.x_org() as i32,
+                               info.y_org() as i32,
+                               info.width() as u32,
+                               info.height() as u32)
+            })
+            .collect();
+
+        if screens.is_empty() { None } else { Some(screens) }
+    }
+
+    /// Get the geometry of the root window as a single screen
+    fn root_screens(&self) -> Vec<Rectangle> {
+        self.connection
+            .get_setup()
+            .roots()
+            .filter(|screen| screen.root() == self.root)
+            .map(|screen| {
+                Rectangle::new(0,
+                               0,
+                               screen.width_in_pixels() as u32,
+                               screen.height_in_pixels() as u32)
+            })
+            .collect()
+    }
+
+    /// Get the geometry of all monitors, preferring RandR over Xinerama
+    /// over the plain X screen. Duplicated and cloned monitors are removed.
+    pub(super) fn query_screens(&self) -> Vec<Rectangle> {
+        let screens = self.randr_screens()
+            .or_else(|| {
+                debug!("falling back to xinerama");
+                self.xinerama_screens()
+            })
+            .unwrap_or_else(|| {
+                debug!("falling back to the root window");
+                self.root_screens()
+            });
+
+        remove_clones(screens)
+    }
+}
@@ -0,0 +1,266 @@
+extern crate sabiwm;
+extern crate serde_json;
+
+use sabiwm::command::Command;
+use sabiwm::ipc::{self, Client, EventKind, Notification, Reply, Request};
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+const USAGE: &str = "Usage: sabictl [--json] [--socket PATH] COMMAND
+
+Commands:
+  get workspaces          List all workspaces
+  get focused             Show the focused window
+  subscribe EVENT...      Print events as they happen, EVENT is one of
+                          workspace, focus, window, title, layout,
+                          screens, config or all
+  workspace NAME          Show the workspace with the given name
+  ACTION [ARGUMENT]       Run a window manager command, e.g.
+                          `focus down`, `view 2` or `spawn xterm`
+
+Options:
+  --json                  Print the raw JSON replies
+  --socket PATH           Connect to PATH instead of the default socket
+
+Exit status:
+  0  success
+  1  the window manager rejected the request
+  2  invalid usage
+  3  unable to talk to the window manager";
+
+/// Exit status if the window manager rejected the request
+const EXIT_REJECTED: i32 = 1;
+/// Exit status for invalid arguments
+const EXIT_USAGE: i32 = 2;
+/// Exit status if the window manager can't be reached
+const EXIT_CONNECTION: i32 = 3;
+
+struct Options {
+    json: bool,
+    socket: Option<PathBuf>,
+    arguments: Vec<String>,
+}
+
+fn fail(status: i32, message: &str) -> ! {
+    eprintln!("sabictl: {}", message);
+    process::exit(status);
+}
+
+fn parse_options() -> Options {
+    let mut options = Options {
+        json: false,
+        socket: None,
+        arguments: Vec::new(),
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "--json" => options.json = true,
+            "--socket" => {
+                let path = args.next().unwrap_or_else(|| fail(EXIT_USAGE, "--socket needs a path"));
+                options.socket = Some(PathBuf::from(path));
+            }
+            _ => options.arguments.push(arg),
+        }
+    }
+
+    if options.arguments.is_empty() {
+        eprintln!("{}", USAGE);
+        process::exit(EXIT_USAGE);
+    }
+    options
+}
+
+/// Parse the command, also accepting the words of
+/// hyphenated commands separately, e.g. `focus down`.
+fn parse_command(arguments: &[String]) -> Command {
+    if let Ok(command) = arguments.join(" ").parse() {
+        return command;
+    }
+
+    if arguments.len() >= 2 {
+        let joined = format!("{}-{}", arguments[0], arguments[1]);
+        let rest = arguments[2..].join(" ");
+        if let Ok(command) = format!("{} {}", joined, rest).parse() {
+            return command;
+        }
+    }
+
+    fail(EXIT_USAGE,
+         &format!("unknown command \"{}\", see --help", arguments.join(" ")))
+}
+
+fn parse_event(name: &str) -> Vec<EventKind> {
+    match name {
+        "all" => {
+            vec![EventKind::Workspace,
+                 EventKind::Focus,
+                 EventKind::Window,
+                 EventKind::Title,
+                 EventKind::Layout,
+                 EventKind::Screens,
+                 EventKind::Config]
+        }
+        _ => {
+            let kind = serde_json::from_value(serde_json::Value::String(name.to_string()));
+            vec![kind.unwrap_or_else(|_| fail(EXIT_USAGE, &format!("unknown event \"{}\"", name)))]
+        }
+    }
+}
+
+fn request(client: &mut Client, request: &Request) -> Reply<u64> {
+    client.request(request).unwrap_or_else(|err| fail(EXIT_CONNECTION, &err.to_string()))
+}
+
+/// Print the reply, either as JSON or in a human readable way
+fn print_reply(reply: &Reply<u64>, json: bool) {
+    if json {
+        println!("{}", serde_json::to_string(reply).unwrap_or_default());
+    } else {
+        match *reply {
+            Reply::Success => (),
+            Reply::Workspaces { ref workspaces } => {
+                for workspace in workspaces {
+                    let screen = workspace.screen
+                        .map_or(String::new(), |s| format!("  screen {}", s));
+                    println!("{} {:<10} {:<12} {} windows{}",
+                             if workspace.focused { '*' } else { ' ' },
+                             workspace.tag,
+                             workspace.layout,
+                             workspace.windows.len(),
+                             screen);
+                }
+            }
+            Reply::Focused { window: Some(ref window) } => {
+                println!("{:#x}  {}  ({})  workspace {}",
+                         window.id,
+                         window.name.as_deref().unwrap_or(""),
+                         window.class.as_deref().unwrap_or(""),
+                         window.workspace);
+            }
+            Reply::Focused { window: None } => println!("no focused window"),
+            Reply::Error { ref message } => eprintln!("sabictl: {}", message),
+        }
+    }
+
+    if let Reply::Error { .. } = *reply {
+        process::exit(EXIT_REJECTED);
+    }
+}
+
+fn describe(notification: &Notification<u64>) -> String {
+    match *notification {
+        Notification::WorkspaceChanged { ref workspace } => {
+            format!("workspace {}", workspace.tag)
+        }
+        Notification::FocusChanged { window: Some(ref window) } => {
+            format!("focus {:#x} {}", window.id, window.name.as_deref().unwrap_or(""))
+        }
+        Notification::FocusChanged { window: None } => String::from("focus none"),
+        Notification::WindowManaged { ref window } => {
+            format!("managed {:#x} {}", window.id, window.name.as_deref().unwrap_or(""))
+        }
+        Notification::WindowUnmanaged { window } => format!("unmanaged {:#x}", window),
+        Notification::TitleChanged { window, ref name } => format!("title {:#x} {}", window, name),
+        Notification::LayoutChanged { workspace, ref layout } => {
+            format!("layout {} {}", workspace, layout)
+        }
+        Notification::ScreensChanged { ref screens } => format!("screens {}", screens.len()),
+        Notification::ConfigReloaded => String::from("config reloaded"),
+    }
+}
+
+fn subscribe(client: &mut Client, events: Vec<EventKind>, json: bool) {
+    let reply = request(client, &Request::Subscribe { events });
+    print_reply(&reply, json);
+
+    loop {
+        let line = client.read_raw_line()
+            .unwrap_or_else(|err| fail(EXIT_CONNECTION, &err.to_string()));
+        let line = match line {
+            Some(line) => line,
+            None => fail(EXIT_CONNECTION, "connection closed by the window manager"),
+        };
+
+        if json {
+            print!("{}", line);
+        } else {
+            match serde_json::from_str(&line) {
+                Ok(notification) => println!("{}", describe(&notification)),
+                Err(_) => print!("{}", line),
+            }
+        }
+    }
+}
+
+/// Find the id of the workspace with the given name
+fn workspace_id(client: &mut Client, name: &str) -> u32 {
+    match request(client, &Request::GetWorkspaces) {
+        Reply::Workspaces { workspaces } => {
+            workspaces.iter()
+                .find(|w| w.tag == name)
+                .map(|w| w.id)
+                .unwrap_or_else(|| fail(EXIT_REJECTED, &format!("no workspace named \"{}\"", name)))
+        }
+        reply => {
+            print_reply(&reply, false);
+            fail(EXIT_REJECTED, "unable to list workspaces")
+        }
+    }
+}
+
+/// What the user asked for, validated before connecting
+enum Action {
+    Request(Request),
+    Subscribe(Vec<EventKind>),
+    Workspace(String),
+}
+
+fn parse_action(arguments: &[String]) -> Action {
+    match arguments[0].as_str() {
+        "get" => {
+            match arguments.get(1).map(|s| s.as_str()) {
+                Some("workspaces") if arguments.len() == 2 => {
+                    Action::Request(Request::GetWorkspaces)
+                }
+                Some("focused") if arguments.len() == 2 => Action::Request(Request::GetFocused),
+                _ => fail(EXIT_USAGE, "expected `get workspaces` or `get focused`"),
+            }
+        }
+        "subscribe" if arguments.len() > 1 => {
+            Action::Subscribe(arguments[1..].iter().flat_map(|e| parse_event(e)).collect())
+        }
+        "subscribe" => fail(EXIT_USAGE, "expected at least one event to subscribe to"),
+        "workspace" if arguments.len() == 2 => Action::Workspace(arguments[1].clone()),
+        _ => Action::Request(Request::Command { command: parse_command(arguments) }),
+    }
+}
+
+fn main() {
+    let options = parse_options();
+    let action = parse_action(&options.arguments);
+
+    let path = options.socket
+        .clone()
+        .map_or_else(ipc::socket_path, Ok)
+        .unwrap_or_else(|err| fail(EXIT_CONNECTION, &err.to_string()));
+    let mut client = Client::connect(&path)
+        .unwrap_or_else(|err| fail(EXIT_CONNECTION, &err.to_string()));
+
+    let reply = match action {
+        Action::Request(ref r) => request(&mut client, r),
+        Action::Subscribe(events) => return subscribe(&mut client, events, options.json),
+        Action::Workspace(ref name) => {
+            let id = workspace_id(&mut client, name);
+            request(&mut client, &Request::Command { command: Command::View(id) })
+        }
+    };
+
+    print_reply(&reply, options.json);
+}
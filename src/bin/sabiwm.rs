@@ -1,10 +1,27 @@
 extern crate sabiwm;
-#[macro_use(o, slog_log, slog_error)]
+#[macro_use(slog_log, slog_error)]
 extern crate slog;
 #[macro_use]
 extern crate slog_scope;
 
+fn check_config() {
+    match ::sabiwm::check_config() {
+        Ok(path) => println!("{}: ok", path.display()),
+        Err(ref e) => {
+            eprintln!("{}", e);
+            for e in e.iter().skip(1) {
+                eprintln!("caused by: {}", e);
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() {
+    if std::env::args().skip(1).any(|arg| arg == "--check-config") {
+        return check_config();
+    }
+
     if let Err(ref e) = ::sabiwm::run() {
         error!("sabiwm stopped: {}", e);
         for e in e.iter().skip(1) {
@@ -0,0 +1,415 @@
+//! Commands are the single way to act on the window manager.
+//! Key bindings, mouse bindings, IPC and scripts all produce a
+//! [`Command`], which is then executed by the [`WindowManager`].
+//! This keeps the behaviour identical no matter how a command
+//! was triggered.
+//!
+//! Commands can be written as plain strings, e.g. `"focus-down"`,
+//! `"view 2"` or `"spawn xterm -e htop"`.
+//!
+//! [`Command`]: enum.Command.html
+//! [`WindowManager`]: ../struct.WindowManager.html
+
+use backend::{StateChange, WindowState};
+use core::StackSet;
+use errors::*;
+use layout::LayoutMessage;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// The names of the [`LayoutMessage`]s when written as a command
+///
+/// [`LayoutMessage`]: ../layout/enum.LayoutMessage.html
+const LAYOUT_MESSAGES: [(&str, LayoutMessage); 7] = [("shrink", LayoutMessage::Shrink),
+                                                     ("expand", LayoutMessage::Expand),
+                                                     ("inc-master", LayoutMessage::IncMaster),
+                                                     ("dec-master", LayoutMessage::DecMaster),
+                                                     ("next-layout", LayoutMessage::NextLayout),
+                                                     ("prev-layout", LayoutMessage::PrevLayout),
+                                                     ("first-layout",
+                                                      LayoutMessage::FirstLayout)];
+
+/// The names of the [`StateChange`]s when written as a command
+///
+/// [`StateChange`]: ../backend/enum.StateChange.html
+const STATE_CHANGES: [(&str, StateChange); 3] = [("add", StateChange::Add),
+                                                 ("remove", StateChange::Remove),
+                                                 ("toggle", StateChange::Toggle)];
+
+/// The names of the [`WindowState`]s when written as a command
+///
+/// [`WindowState`]: ../backend/enum.WindowState.html
+const WINDOW_STATES: [(&str, WindowState); 2] = [("fullscreen", WindowState::Fullscreen),
+                                                 ("demands-attention",
+                                                  WindowState::DemandsAttention)];
+
+/// Looks up the value with the given name
+fn named<T: Copy>(names: &[(&str, T)], name: Option<&&str>) -> Option<T> {
+    name.and_then(|name| names.iter().find(|&&(n, _)| n == *name)).map(|&(_, value)| value)
+}
+
+/// Looks up the name of the given value
+fn name_of<T: PartialEq>(names: &[(&'static str, T)], value: &T) -> &'static str {
+    names.iter().find(|&(_, v)| v == value).map_or("", |&(n, _)| n)
+}
+
+/// An action the window manager can execute
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::backend::{StateChange, WindowState};
+/// # use sabiwm::command::Command;
+/// # use sabiwm::layout::LayoutMessage;
+/// assert_eq!(Command::FocusDown, "focus-down".parse().unwrap());
+/// assert_eq!(Command::View(2), "view 2".parse().unwrap());
+/// assert_eq!(Command::SendLayoutMessage(LayoutMessage::Shrink),
+///            "shrink".parse().unwrap());
+/// assert_eq!(Command::Spawn(String::from("xterm -e htop")),
+///            "spawn xterm -e htop".parse().unwrap());
+///
+/// assert_eq!(Command::ToggleStruts, "toggle-struts".parse().unwrap());
+/// assert_eq!(Command::FocusWindow(4194307), "focus-window 4194307".parse().unwrap());
+/// assert_eq!(Command::SetWindowState(4194307, StateChange::Toggle, WindowState::Fullscreen),
+///            "set-window-state 4194307 toggle fullscreen".parse().unwrap());
+///
+/// assert_eq!("shift 3", Command::Shift(3).to_string());
+/// assert_eq!("shift-window 4194307 3", Command::ShiftWindow(4194307, 3).to_string());
+/// assert!("view".parse::<Command>().is_err());
+/// assert!("fly-away".parse::<Command>().is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Move the focus to the previous window
+    FocusUp,
+    /// Move the focus to the next window
+    FocusDown,
+    /// Focus the window with the given id, showing
+    /// its workspace if necessary
+    FocusWindow(u32),
+    /// Swap the focused window with the previous one
+    SwapUp,
+    /// Swap the focused window with the next one
+    SwapDown,
+    /// Swap the focused window with the master window
+    SwapMaster,
+    /// Put the focused floating window back into the tiling
+    Sink,
+    /// Close the focused window
+    Kill,
+    /// Close the window with the given id
+    CloseWindow(u32),
+    /// Run the given shell command
+    Spawn(String),
+    /// Show the workspace with the given id, focusing
+    /// the screen it is visible on if any
+    View(u32),
+    /// Show the workspace with the given id on the current screen
+    GreedyView(u32),
+    /// Move the focused window to the workspace with the given id
+    Shift(u32),
+    /// Move the window with the first id to the
+    /// workspace with the second id
+    ShiftWindow(u32, u32),
+    /// Change a state of the window with the given id
+    SetWindowState(u32, StateChange, WindowState),
+    /// Focus the screen with the given id
+    FocusScreen(u32),
+    /// Send a message to the layout of the current workspace
+    SendLayoutMessage(LayoutMessage),
+    /// Let the current workspace cover the space reserved by
+    /// docks and panels, or reserve it again
+    ToggleStruts,
+    /// Stop the window manager
+    Quit,
+    /// Replace the window manager with a new instance of itself
+    Restart,
+    /// Reload the configuration file
+    Reload,
+}
+
+impl Command {
+    /// Apply the command to the given [`StackSet`]. Commands that don't
+    /// change the [`StackSet`] but have side effects, e.g. spawning a
+    /// process, leave it alone and are handled by the [`WindowManager`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::command::Command;
+    /// # use sabiwm::core::StackSet;
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2"], 1).insert(1).insert(2);
+    /// assert_eq!(Some(2), stack_set.peek());
+    ///
+    /// let stack_set = Command::FocusDown.apply(&stack_set).unwrap();
+    /// assert_eq!(Some(1), stack_set.peek());
+    ///
+    /// let stack_set = Command::Shift(1).apply(&stack_set).unwrap();
+    /// assert_eq!(Some(1), stack_set.find_workspace(1));
+    ///
+    /// assert!(Command::Quit.apply(&stack_set).is_none());
+    /// ```
+    ///
+    /// # Arguments
+    /// `stack_set` - The current window manager state
+    ///
+    /// # Return value
+    /// The new [`StackSet`] or `None` if the command does not operate on it
+    ///
+    /// [`StackSet`]: ../core/struct.StackSet.html
+    /// [`WindowManager`]: ../struct.WindowManager.html
+    pub fn apply<Window>(&self, stack_set: &StackSet<Window>) -> Option<StackSet<Window>>
+        where Window: Copy + Clone + PartialEq + Eq + Debug + Hash + From<u32> + 'static
+    {
+        match *self {
+            Command::FocusUp => Some(stack_set.focus_up()),
+            Command::FocusDown => Some(stack_set.focus_down()),
+            Command::FocusWindow(window) => Some(stack_set.focus_window(Window::from(window))),
+            Command::SwapUp => Some(stack_set.swap_up()),
+            Command::SwapDown => Some(stack_set.swap_down()),
+            Command::SwapMaster => Some(stack_set.swap_master()),
+            Command::Sink => {
+                Some(stack_set.peek().map_or_else(|| stack_set.clone(), |w| stack_set.sink(w)))
+            }
+            Command::View(id) => Some(stack_set.view(id)),
+            Command::GreedyView(id) => Some(stack_set.greedy_view(id)),
+            Command::Shift(id) => Some(stack_set.shift(id)),
+            Command::ShiftWindow(window, id) => {
+                Some(stack_set.shift_window(id, Window::from(window)))
+            }
+            Command::FocusScreen(id) => Some(stack_set.view_screen(id)),
+            Command::SendLayoutMessage(message) => {
+                Some(stack_set.map_workspace(|w| w.send_message(message)))
+            }
+            Command::Kill | Command::CloseWindow(_) | Command::SetWindowState(..) |
+            Command::Spawn(_) | Command::ToggleStruts | Command::Quit | Command::Restart |
+            Command::Reload => None,
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = Error;
+
+    fn from_str(command: &str) -> Result<Command> {
+        let command = command.trim();
+        let (name, argument) = match command.find(char::is_whitespace) {
+            Some(index) => (&command[..index], command[index..].trim()),
+            None => (command, ""),
+        };
+
+        let arguments: Vec<&str> = argument.split_whitespace().collect();
+        let id = |index: usize| -> Result<u32> {
+            arguments.get(index)
+                .and_then(|a| a.parse().ok())
+                .ok_or_else(|| format!("command {:?} needs a numeric argument", name).into())
+        };
+        let change = || -> Result<StateChange> {
+            named(&STATE_CHANGES, arguments.get(1))
+                .ok_or_else(|| format!("command {:?} needs add, remove or toggle", name).into())
+        };
+        let state = || -> Result<WindowState> {
+            named(&WINDOW_STATES, arguments.get(2))
+                .ok_or_else(|| format!("command {:?} needs a window state", name).into())
+        };
+
+        let parsed = match name {
+            "focus-up" => Command::FocusUp,
+            "focus-down" => Command::FocusDown,
+            "focus-window" => Command::FocusWindow(id(0)?),
+            "swap-up" => Command::SwapUp,
+            "swap-down" => Command::SwapDown,
+            "swap-master" => Command::SwapMaster,
+            "sink" => Command::Sink,
+            "kill" => Command::Kill,
+            "close-window" => Command::CloseWindow(id(0)?),
+            "spawn" if !argument.is_empty() => Command::Spawn(String::from(argument)),
+            "spawn" => bail!("command \"spawn\" needs a program to run"),
+            "view" => Command::View(id(0)?),
+            "greedy-view" => Command::GreedyView(id(0)?),
+            "shift" => Command::Shift(id(0)?),
+            "shift-window" => Command::ShiftWindow(id(0)?, id(1)?),
+            "set-window-state" => Command::SetWindowState(id(0)?, change()?, state()?),
+            "focus-screen" => Command::FocusScreen(id(0)?),
+            "toggle-struts" => Command::ToggleStruts,
+            "quit" => Command::Quit,
+            "restart" => Command::Restart,
+            "reload" => Command::Reload,
+            _ => {
+                match named(&LAYOUT_MESSAGES, Some(&name)) {
+                    Some(message) => Command::SendLayoutMessage(message),
+                    None => bail!("unknown command {:?}", name),
+                }
+            }
+        };
+
+        let count = match parsed {
+            Command::Spawn(_) => arguments.len(),
+            Command::FocusWindow(_) | Command::CloseWindow(_) | Command::View(_) |
+            Command::GreedyView(_) | Command::Shift(_) | Command::FocusScreen(_) => 1,
+            Command::ShiftWindow(..) => 2,
+            Command::SetWindowState(..) => 3,
+            _ => 0,
+        };
+        if arguments.len() > count {
+            bail!("command {:?} takes {} argument(s)", name, count);
+        }
+
+        Ok(parsed)
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Command::FocusUp => write!(f, "focus-up"),
+            Command::FocusDown => write!(f, "focus-down"),
+            Command::FocusWindow(window) => write!(f, "focus-window {}", window),
+            Command::SwapUp => write!(f, "swap-up"),
+            Command::SwapDown => write!(f, "swap-down"),
+            Command::SwapMaster => write!(f, "swap-master"),
+            Command::Sink => write!(f, "sink"),
+            Command::Kill => write!(f, "kill"),
+            Command::CloseWindow(window) => write!(f, "close-window {}", window),
+            Command::Spawn(ref program) => write!(f, "spawn {}", program),
+            Command::View(id) => write!(f, "view {}", id),
+            Command::GreedyView(id) => write!(f, "greedy-view {}", id),
+            Command::Shift(id) => write!(f, "shift {}", id),
+            Command::ShiftWindow(window, id) => write!(f, "shift-window {} {}", window, id),
+            Command::SetWindowState(window, change, state) => {
+                write!(f,
+                       "set-window-state {} {} {}",
+                       window,
+                       name_of(&STATE_CHANGES, &change),
+                       name_of(&WINDOW_STATES, &state))
+            }
+            Command::FocusScreen(id) => write!(f, "focus-screen {}", id),
+            Command::SendLayoutMessage(message) => {
+                write!(f, "{}", name_of(&LAYOUT_MESSAGES, &message))
+            }
+            Command::ToggleStruts => write!(f, "toggle-struts"),
+            Command::Quit => write!(f, "quit"),
+            Command::Restart => write!(f, "restart"),
+            Command::Reload => write!(f, "reload"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Command {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Command {
+    fn serialize<S: Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use backend::{StateChange, WindowState};
+    use core::StackSet;
+    use layout::LayoutMessage;
+    use super::Command;
+
+    /// Two screens showing the first two of four workspaces,
+    /// the windows are added to the first one
+    fn stack_set(windows: &[u32]) -> StackSet<u32> {
+        windows.iter().fold(StackSet::new(vec!["1", "2", "3", "4"], 2), |s, &w| s.insert(w))
+    }
+
+    #[test]
+    fn commands_are_parsed_from_their_names() {
+        let commands = [("focus-up", Command::FocusUp),
+                        ("  view 3 ", Command::View(3)),
+                        ("spawn xterm -e htop", Command::Spawn(String::from("xterm -e htop"))),
+                        ("shift-window 7 2", Command::ShiftWindow(7, 2)),
+                        ("set-window-state 7 toggle fullscreen",
+                         Command::SetWindowState(7, StateChange::Toggle, WindowState::Fullscreen)),
+                        ("expand", Command::SendLayoutMessage(LayoutMessage::Expand))];
+        for &(text, ref command) in &commands {
+            assert_eq!(*command, text.parse::<Command>().unwrap());
+            assert_eq!(*command, command.to_string().parse::<Command>().unwrap());
+        }
+    }
+
+    #[test]
+    fn invalid_commands_are_rejected() {
+        let invalid = ["",
+                       "jump",
+                       "view",
+                       "view two",
+                       "focus-up 1",
+                       "spawn",
+                       "shift-window 7",
+                       "set-window-state 7 flip fullscreen",
+                       "set-window-state 7 add maximized"];
+        for text in &invalid {
+            assert!(text.parse::<Command>().is_err(), "{:?} was accepted", text);
+        }
+    }
+
+    #[test]
+    fn focus_commands_move_the_focus() {
+        let stack_set = stack_set(&[1, 2, 3]);
+        assert_eq!(Some(3), stack_set.peek());
+
+        let focused = Command::FocusDown.apply(&stack_set).unwrap();
+        // The window added first is laid out right after the newest one
+        assert_eq!(Some(1), focused.peek());
+        let focused = Command::FocusUp.apply(&focused).unwrap();
+        assert_eq!(Some(3), focused.peek());
+        let focused = Command::FocusWindow(1).apply(&stack_set).unwrap();
+        assert_eq!(Some(1), focused.peek());
+    }
+
+    #[test]
+    fn workspace_commands_change_the_workspaces() {
+        let stack_set = stack_set(&[1, 2]);
+
+        let viewed = Command::View(2).apply(&stack_set).unwrap();
+        assert_eq!(2, viewed.current.workspace.id);
+
+        let shifted = Command::Shift(3).apply(&stack_set).unwrap();
+        assert_eq!(Some(3), shifted.find_workspace(2));
+        assert_eq!(Some(0), shifted.find_workspace(1));
+
+        let shifted = Command::ShiftWindow(1, 1).apply(&stack_set).unwrap();
+        assert_eq!(Some(1), shifted.find_workspace(1));
+
+        let focused = Command::FocusScreen(1).apply(&stack_set).unwrap();
+        assert_eq!(1, focused.current.screen_id);
+    }
+
+    #[test]
+    fn layout_messages_reach_the_current_layout() {
+        let stack_set = stack_set(&[1]);
+        let name = stack_set.current.workspace.layout.name();
+
+        let changed = Command::SendLayoutMessage(LayoutMessage::NextLayout)
+            .apply(&stack_set)
+            .unwrap();
+        assert!(name != changed.current.workspace.layout.name());
+    }
+
+    #[test]
+    fn commands_with_side_effects_are_left_to_the_window_manager() {
+        let stack_set = stack_set(&[1]);
+        let commands = [Command::Kill,
+                        Command::CloseWindow(1),
+                        Command::Spawn(String::from("xterm")),
+                        Command::ToggleStruts,
+                        Command::Quit,
+                        Command::Restart,
+                        Command::Reload];
+        for command in &commands {
+            assert!(command.apply(&stack_set).is_none(), "{} was applied", command);
+        }
+    }
+}
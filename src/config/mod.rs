@@ -0,0 +1,566 @@
+//! The configuration of the window manager, read from a TOML file
+//! at `$XDG_CONFIG_HOME/sabiwm/config.toml`. Every setting is
+//! optional, anything missing falls back to the defaults.
+//!
+//! ```toml
+//! workspaces = ["web", "code", "chat"]
+//! gap = 4
+//! layouts = ["tall", "full"]
+//!
+//! [border]
+//! width = 2
+//! focused = "#268bd2"
+//! normal = "#444444"
+//!
+//! # Added to, or replacing, the default key bindings.
+//! # "none" removes a default binding.
+//! [keys]
+//! "Mod4+Shift+Return" = "spawn urxvt"
+//! "Mod4+f" = "next-layout"
+//! "Mod4+q" = "none"
+//!
+//! [[rules]]
+//! class = "mpv"
+//! float = true
+//!
+//! # Dialogs, utility windows and splash screens float by
+//! # default, notifications and tooltips are not managed
+//! [[rules]]
+//! type = "splash"
+//! manage = false
+//!
+//! [[rules]]
+//! class = "Firefox"
+//! workspace = 0
+//!
+//! [[rules]]
+//! class = "URxvt"
+//! ignore-size-hints = true
+//! ```
+//!
+//! A status line for bars like xmobar, dzen2 or lemonbar is written
+//! on every change if the `[log-hook]` section sets an output:
+//!
+//! ```toml
+//! [log-hook]
+//! output = "/tmp/sabiwm-status"
+//! format = "{workspaces} | {layout} | {title}"
+//! current = "<fc=#268bd2>[{name}]</fc>"
+//! ```
+//!
+//! Setting `i3-ipc = true` additionally serves the IPC protocol of
+//! i3, for tools like polybar's i3 module.
+//!
+//! Windows are asked to close by the `kill` command. With
+//! `kill-timeout = 5`, windows still open after five seconds, or
+//! asked a second time, get killed.
+//!
+//! The configuration is reloaded on `SIGHUP`, the `reload`
+//! command and whenever the file changes.
+
+mod watcher;
+
+pub(crate) use config::watcher::ConfigWatcher;
+
+use backend::{KeyCommand, WindowType};
+use command::Command;
+use errors::*;
+use layout::{self, Choose, Full, Layout, Mirror, Tall};
+use serde::de::{self, Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use toml;
+use xdg::BaseDirectories;
+
+/// The default key bindings, all on the super key. Workspace
+/// bindings are added for the digits 1 to 9.
+const DEFAULT_KEYS: [(&str, &str); 17] = [("Mod4+j", "focus-down"),
+                                          ("Mod4+k", "focus-up"),
+                                          ("Mod4+Shift+j", "swap-down"),
+                                          ("Mod4+Shift+k", "swap-up"),
+                                          ("Mod4+Return", "swap-master"),
+                                          ("Mod4+t", "sink"),
+                                          ("Mod4+Shift+c", "kill"),
+                                          ("Mod4+Shift+Return", "spawn xterm"),
+                                          ("Mod4+h", "shrink"),
+                                          ("Mod4+l", "expand"),
+                                          ("Mod4+comma", "inc-master"),
+                                          ("Mod4+period", "dec-master"),
+                                          ("Mod4+space", "next-layout"),
+                                          ("Mod4+b", "toggle-struts"),
+                                          ("Mod4+Shift+q", "quit"),
+                                          ("Mod4+q", "restart"),
+                                          ("Mod4+Shift+r", "reload")];
+
+/// Returns the path of the configuration file, whether it exists or not
+pub fn path() -> Result<PathBuf> {
+    let xdg = BaseDirectories::with_prefix("sabiwm")
+        .chain_err(|| "unable to get xdg base directory")?;
+    Ok(xdg.get_config_home().join("config.toml"))
+}
+
+/// A color in the `"#rrggbb"` notation
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::config::Color;
+/// assert_eq!(Color(0x268bd2), "#268bd2".parse().unwrap());
+/// assert!("268bd2".parse::<Color>().is_err());
+/// assert!("#268bd".parse::<Color>().is_err());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Color(pub u32);
+
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(color: &str) -> Result<Color> {
+        if color.len() != 7 || !color.starts_with('#') {
+            bail!("invalid color {:?}, expected \"#rrggbb\"", color);
+        }
+
+        u32::from_str_radix(&color[1..], 16)
+            .map(Color)
+            .chain_err(|| format!("invalid color {:?}, expected \"#rrggbb\"", color))
+    }
+}
+
+/// The border drawn around every window
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Border {
+    /// Width of the border in pixels
+    pub width: u32,
+    /// Color of the focused window's border
+    pub focused: Color,
+    /// Color of the border of all other windows
+    pub normal: Color,
+}
+
+impl Default for Border {
+    fn default() -> Border {
+        Border {
+            width: 1,
+            focused: Color(0x268bd2),
+            normal: Color(0x444444),
+        }
+    }
+}
+
+/// The layouts that can be selected in the configuration
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LayoutKind {
+    /// A [`Tall`] layout with the master area on the left
+    ///
+    /// [`Tall`]: ../layout/struct.Tall.html
+    Tall,
+    /// A mirrored [`Tall`] layout with the master area on top
+    ///
+    /// [`Tall`]: ../layout/struct.Tall.html
+    Wide,
+    /// The [`Full`] layout, showing only the focused window
+    ///
+    /// [`Full`]: ../layout/struct.Full.html
+    Full,
+}
+
+impl LayoutKind {
+    /// Create the [`Layout`] of this kind with its default settings
+    ///
+    /// [`Layout`]: ../layout/trait.Layout.html
+    pub fn layout<Window>(&self) -> Box<dyn Layout<Window>>
+        where Window: Copy + Clone + Eq + Debug + 'static
+    {
+        match *self {
+            LayoutKind::Tall => Box::new(Tall::default()),
+            LayoutKind::Wide => Box::new(Mirror::new(Box::new(Tall::default()))),
+            LayoutKind::Full => Box::new(Full),
+        }
+    }
+}
+
+/// A rule for newly created windows. All given conditions must
+/// match for the actions to be applied. Actions that are not
+/// set keep the default handling of the [`WindowType`].
+///
+/// [`WindowType`]: ../backend/enum.WindowType.html
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rule {
+    /// Match windows with this class
+    pub class: Option<String>,
+    /// Match windows with this name/title
+    pub name: Option<String>,
+    /// Match windows of this type, e.g. `"dialog"` or `"splash"`
+    #[serde(rename = "type")]
+    pub window_type: Option<WindowType>,
+    /// Move matching windows to the workspace with this id
+    pub workspace: Option<u32>,
+    /// Let matching windows float, or tile them
+    pub float: Option<bool>,
+    /// Manage matching windows, or leave them alone
+    pub manage: Option<bool>,
+    /// Tile matching windows without respecting their size hints,
+    /// e.g. so terminals fill their whole slot
+    #[serde(rename = "ignore-size-hints")]
+    pub ignore_size_hints: bool,
+}
+
+impl Rule {
+    /// Checks if the rule applies to a window with the given class, name and type
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::backend::WindowType;
+    /// # use sabiwm::config::Rule;
+    /// let rule = Rule { class: Some(String::from("mpv")), ..Rule::default() };
+    /// assert!(rule.matches(Some("mpv"), Some("video.mkv"), WindowType::Normal));
+    /// assert!(!rule.matches(Some("xterm"), Some("video.mkv"), WindowType::Normal));
+    /// assert!(!rule.matches(None, None, WindowType::Normal));
+    ///
+    /// let rule = Rule { window_type: Some(WindowType::Splash), ..rule };
+    /// assert!(rule.matches(Some("mpv"), None, WindowType::Splash));
+    /// assert!(!rule.matches(Some("mpv"), None, WindowType::Dialog));
+    /// ```
+    pub fn matches(&self,
+                   class: Option<&str>,
+                   name: Option<&str>,
+                   window_type: WindowType)
+                   -> bool {
+        let matches = |expected: &Option<String>, actual: Option<&str>| {
+            expected.as_ref().is_none_or(|e| Some(e.as_str()) == actual)
+        };
+        matches(&self.class, class) && matches(&self.name, name) &&
+        self.window_type.is_none_or(|t| t == window_type)
+    }
+}
+
+/// How a workspace is shown in the status line of the [`LogHook`]
+///
+/// [`LogHook`]: struct.LogHook.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WorkspaceState {
+    /// The focused workspace
+    Current,
+    /// A workspace visible on another screen
+    Visible,
+    /// A hidden workspace with windows on it
+    Hidden,
+    /// A hidden workspace without any windows
+    Empty,
+    /// A workspace that is not focused, but has a window demanding attention
+    Urgent,
+}
+
+/// The status line written on every change of the window manager's
+/// state, similar to xmonad's log hook. The templates may contain
+/// the markup of the status bar.
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::config::{LogHook, WorkspaceState};
+/// let hook = LogHook {
+///     format: String::from("{workspaces} | {title}"),
+///     urgent: String::from("^fg(red){name}^fg()"),
+///     ..LogHook::default()
+/// };
+/// let workspaces = [("web", WorkspaceState::Current),
+///                   ("code", WorkspaceState::Visible),
+///                   ("chat", WorkspaceState::Urgent),
+///                   ("mail", WorkspaceState::Hidden),
+///                   ("misc", WorkspaceState::Empty)];
+/// assert_eq!("[web] <code> ^fg(red)chat^fg() mail | {name}",
+///            hook.format(&workspaces, "Tall", "{name}"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogHook {
+    /// Where to write the status line: `"-"` for stdout, otherwise the
+    /// path of a FIFO or of a file that always holds the latest line.
+    /// Nothing is written if it is not set.
+    pub output: Option<String>,
+    /// The template of the whole line, `{workspaces}`, `{layout}`
+    /// and `{title}` are replaced with the current values
+    pub format: String,
+    /// The template of the focused workspace, `{name}` is replaced
+    /// with its name
+    pub current: String,
+    /// The template of workspaces visible on other screens
+    pub visible: String,
+    /// The template of hidden workspaces with windows
+    pub hidden: String,
+    /// The template of hidden workspaces without windows,
+    /// they are left out if it is empty
+    pub empty: String,
+    /// The template of workspaces with a window demanding attention
+    pub urgent: String,
+    /// The text between two workspaces
+    pub separator: String,
+}
+
+impl LogHook {
+    /// Format the status line
+    ///
+    /// # Arguments
+    /// `workspaces` - The name and state of every workspace
+    /// `layout` - The name of the current layout
+    /// `title` - The title of the focused window
+    pub fn format(&self,
+                  workspaces: &[(&str, WorkspaceState)],
+                  layout: &str,
+                  title: &str)
+                  -> String {
+        let workspaces: Vec<String> = workspaces.iter()
+            .map(|&(name, state)| {
+                let template = match state {
+                    WorkspaceState::Current => &self.current,
+                    WorkspaceState::Visible => &self.visible,
+                    WorkspaceState::Hidden => &self.hidden,
+                    WorkspaceState::Empty => &self.empty,
+                    WorkspaceState::Urgent => &self.urgent,
+                };
+                fill(template, &[("name", name)])
+            })
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        fill(&self.format,
+             &[("workspaces", &workspaces.join(&self.separator)),
+               ("layout", layout),
+               ("title", title)])
+    }
+}
+
+impl Default for LogHook {
+    fn default() -> LogHook {
+        LogHook {
+            output: None,
+            format: String::from("{workspaces} : {layout} : {title}"),
+            current: String::from("[{name}]"),
+            visible: String::from("<{name}>"),
+            hidden: String::from("{name}"),
+            empty: String::new(),
+            urgent: String::from("*{name}"),
+            separator: String::from(" "),
+        }
+    }
+}
+
+/// Replace the `{key}` placeholders of the template in a single
+/// pass, so the values can't introduce new placeholders
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let placeholder = values.iter()
+            .find(|&&(key, _)| rest.strip_prefix(key).is_some_and(|r| r.starts_with('}')));
+        match placeholder {
+            Some(&(key, value)) => {
+                filled.push_str(value);
+                rest = &rest[key.len() + 1..];
+            }
+            None => filled.push('{'),
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// The complete configuration
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::backend::KeyCommand;
+/// # use sabiwm::command::Command;
+/// # use sabiwm::config::Config;
+/// let config: Config = "gap = 4\n[keys]\n\"Mod4+x\" = \"spawn xterm\"".parse().unwrap();
+/// assert_eq!(4, config.gap);
+/// let key: KeyCommand = "Mod4+x".parse().unwrap();
+/// assert_eq!(Some(&Command::Spawn(String::from("xterm"))), config.keys.get(&key));
+///
+/// let config: Config = "[keys]\n\"Mod4+q\" = \"none\"".parse().unwrap();
+/// let key: KeyCommand = "Mod4+q".parse().unwrap();
+/// assert!(!config.keys.contains_key(&key));
+///
+/// let error = "gap = 4\n[border]\nwidth = \"thick\"".parse::<Config>().unwrap_err();
+/// assert!(error.to_string().contains("border.width"));
+/// assert!(error.to_string().contains("line 3"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The names of the workspaces
+    pub workspaces: Vec<String>,
+    /// The key bindings, merged with the default ones.
+    /// Binding a key to `"none"` removes its default binding.
+    #[serde(deserialize_with = "deserialize_keys")]
+    pub keys: HashMap<KeyCommand, Command>,
+    /// The window border
+    pub border: Border,
+    /// The gap around each tiled window in pixels
+    pub gap: u32,
+    /// The layouts every workspace starts with, switchable
+    /// with the `next-layout` command
+    pub layouts: Vec<LayoutKind>,
+    /// The rules applied to new windows, the first match wins
+    pub rules: Vec<Rule>,
+    /// The status line written on every change
+    #[serde(rename = "log-hook")]
+    pub log_hook: LogHook,
+    /// Whether to serve the IPC protocol of i3 on a second socket
+    #[serde(rename = "i3-ipc")]
+    pub i3_ipc: bool,
+    /// The seconds a window gets to close after being asked to,
+    /// before its process is killed. Asking twice kills it right
+    /// away. Without a timeout, windows are never killed.
+    #[serde(rename = "kill-timeout")]
+    pub kill_timeout: Option<u64>,
+}
+
+impl Config {
+    /// Load the configuration from the XDG config directory.
+    /// If there is no configuration file, the defaults are used.
+    pub fn load() -> Result<Config> {
+        let path = path()?;
+        if !path.exists() {
+            info!("no config file at {}, using defaults", path.display());
+            return Ok(Config::default());
+        }
+        Config::from_file(&path)
+    }
+
+    /// Load the configuration from the given file
+    ///
+    /// # Return value
+    /// The configuration or an error pointing to the offending line and key
+    pub fn from_file(path: &Path) -> Result<Config> {
+        info!("loading config file {}", path.display());
+        let mut content = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut content))
+            .chain_err(|| format!("unable to read config file {}", path.display()))?;
+        content.parse()
+            .map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+
+    /// Create the [`Layout`] for new workspaces from the configured layouts
+    ///
+    /// [`Layout`]: ../layout/trait.Layout.html
+    pub fn layout<Window>(&self) -> Box<dyn Layout<Window>>
+        where Window: Copy + Clone + Eq + Debug + 'static
+    {
+        match self.layouts.len() {
+            0 => layout::default_layout(),
+            1 => self.layouts[0].layout(),
+            _ => Box::new(Choose::new(self.layouts.iter().map(|l| l.layout()).collect())),
+        }
+    }
+
+    /// Find the first rule matching a window with the given class, name and type
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::backend::WindowType;
+    /// # use sabiwm::config::Config;
+    /// let config: Config = "[[rules]]\ntype = \"splash\"\nfloat = false".parse().unwrap();
+    /// let rule = config.rule(Some("gimp"), None, WindowType::Splash).unwrap();
+    /// assert_eq!(Some(false), rule.float);
+    /// assert!(config.rule(Some("gimp"), None, WindowType::Normal).is_none());
+    /// ```
+    pub fn rule(&self,
+                class: Option<&str>,
+                name: Option<&str>,
+                window_type: WindowType)
+                -> Option<&Rule> {
+        self.rules.iter().find(|r| r.matches(class, name, window_type))
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            workspaces: (1..10).map(|i| i.to_string()).collect(),
+            keys: default_keys(),
+            border: Border::default(),
+            gap: 0,
+            layouts: vec![LayoutKind::Tall, LayoutKind::Wide, LayoutKind::Full],
+            rules: Vec::new(),
+            log_hook: LogHook::default(),
+            i3_ipc: false,
+            kill_timeout: None,
+        }
+    }
+}
+
+impl FromStr for Config {
+    type Err = Error;
+
+    fn from_str(content: &str) -> Result<Config> {
+        let config: Config = toml::from_str(content).map_err(|err| err.to_string())?;
+        if config.workspaces.is_empty() {
+            bail!("at least one workspace is needed for key `workspaces`");
+        }
+        Ok(config)
+    }
+}
+
+/// The default key bindings, including `Mod4+<n>` to view
+/// and `Mod4+Shift+<n>` to shift to the n-th workspace.
+fn default_keys() -> HashMap<KeyCommand, Command> {
+    let workspaces = (1..10u32).flat_map(|i| {
+        vec![(format!("Mod4+{}", i), Command::View(i - 1)),
+             (format!("Mod4+Shift+{}", i), Command::Shift(i - 1))]
+    });
+
+    DEFAULT_KEYS.iter()
+        .map(|&(key, command)| (key.to_string(), command.parse().expect("invalid default command")))
+        .chain(workspaces)
+        .map(|(key, command)| (key.parse().expect("invalid default key binding"), command))
+        .collect()
+}
+
+fn deserialize_keys<'de, D>(deserializer: D)
+                            -> ::std::result::Result<HashMap<KeyCommand, Command>, D::Error>
+    where D: Deserializer<'de>
+{
+    let mut keys = default_keys();
+    for (key, binding) in HashMap::<KeyCommand, Binding>::deserialize(deserializer)? {
+        match binding.0 {
+            Some(command) => keys.insert(key, command),
+            None => keys.remove(&key),
+        };
+    }
+    Ok(keys)
+}
+
+/// A key binding in the configuration, `None` if
+/// the key is bound to `"none"`
+struct Binding(Option<Command>);
+
+impl<'de> Deserialize<'de> for Binding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        let binding = String::deserialize(deserializer)?;
+        if binding.trim() == "none" {
+            return Ok(Binding(None));
+        }
+        binding.parse().map(|c| Binding(Some(c))).map_err(de::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,120 @@
+//! Watching for requests to reload the configuration, either
+//! through `SIGHUP` or through changes of the configuration file.
+
+use errors::*;
+use inotify::{Inotify, WatchMask};
+use signal_hook::consts::SIGHUP;
+use signal_hook::low_level::pipe;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{ErrorKind, Read};
+use std::os::fd::{AsFd, BorrowedFd};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+/// Watches the configuration file with inotify and listens for
+/// `SIGHUP`. Both are exposed as file descriptors, so they can
+/// be polled together with the backend's events.
+pub(crate) struct ConfigWatcher {
+    /// The inotify instance and the name of the watched file,
+    /// if the file can be watched
+    file: Option<(Inotify, OsString)>,
+    signals: UnixStream,
+}
+
+impl ConfigWatcher {
+    /// Start listening for `SIGHUP` and watching the given configuration
+    /// file. Not being able to watch the file is not an error, as
+    /// `SIGHUP` still works without it.
+    pub(crate) fn new(path: Option<&Path>) -> Result<ConfigWatcher> {
+        // Registered first, as the default action of SIGHUP
+        // would terminate the window manager
+        let (signals, sender) = UnixStream::pair().chain_err(|| "unable to create signal pipe")?;
+        signals.set_nonblocking(true).chain_err(|| "unable to set up signal pipe")?;
+        pipe::register(SIGHUP, sender).chain_err(|| "unable to register SIGHUP handler")?;
+
+        let file = path.and_then(|path| match ConfigWatcher::watch(path) {
+            Ok(file) => {
+                debug!("watching config file {}", path.display());
+                Some(file)
+            }
+            Err(err) => {
+                warn!("not watching for config changes: {}", err);
+                None
+            }
+        });
+
+        Ok(ConfigWatcher { file, signals })
+    }
+
+    /// Watch the directory of the given configuration file with inotify
+    fn watch(path: &Path) -> Result<(Inotify, OsString)> {
+        let (directory, file_name) = match (path.parent(), path.file_name()) {
+            (Some(directory), Some(file_name)) => (directory, file_name.to_os_string()),
+            _ => bail!("invalid config file path {}", path.display()),
+        };
+
+        // Editors often replace the file instead of writing to it,
+        // so watch the directory to catch those changes as well.
+        fs::create_dir_all(directory)
+            .chain_err(|| format!("unable to create config directory {}", directory.display()))?;
+        let inotify = Inotify::init().chain_err(|| "unable to initialize inotify")?;
+        inotify.watches()
+            .add(directory,
+                 WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::CREATE |
+                 WatchMask::DELETE)
+            .chain_err(|| format!("unable to watch config directory {}", directory.display()))?;
+        Ok((inotify, file_name))
+    }
+
+    /// The file descriptors signalling a possible reload request
+    pub(crate) fn fds(&self) -> Vec<BorrowedFd<'_>> {
+        let mut fds = vec![self.signals.as_fd()];
+        if let Some((ref inotify, _)) = self.file {
+            fds.push(inotify.as_fd());
+        }
+        fds
+    }
+
+    /// Consume all pending notifications
+    ///
+    /// # Return value
+    /// `true` if the configuration should be reloaded
+    pub(crate) fn reload_requested(&mut self) -> bool {
+        let mut reload = false;
+
+        let mut buffer = [0; 64];
+        while let Ok(read) = self.signals.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            info!("received SIGHUP");
+            reload = true;
+        }
+
+        let (inotify, file_name) = match self.file {
+            Some((ref mut inotify, ref file_name)) => (inotify, file_name),
+            None => return reload,
+        };
+        let mut buffer = [0; 4096];
+        loop {
+            match inotify.read_events(&mut buffer) {
+                Ok(events) => {
+                    for event in events {
+                        if event.name == Some(file_name.as_os_str()) {
+                            info!("config file changed");
+                            reload = true;
+                        }
+                    }
+                }
+                Err(ref err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    error!("unable to read inotify events: {}", err);
+                    break;
+                }
+            }
+        }
+
+        reload
+    }
+}
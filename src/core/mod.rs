@@ -5,10 +5,16 @@
 
 mod rectangle;
 mod screen;
+mod size_hints;
 mod stack;
+mod stackset;
+mod strut;
 mod workspace;
 
 pub use core::rectangle::Rectangle;
 pub use core::screen::Screen;
+pub use core::size_hints::{Gravity, SizeHints};
 pub use core::stack::Stack;
+pub use core::stackset::StackSet;
+pub use core::strut::{Edge, Strut};
 pub use core::workspace::Workspace;
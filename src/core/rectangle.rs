@@ -1,5 +1,5 @@
 /// A simple rectangle. Not much to talk about.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Rectangle {
     x: i32,
     y: i32,
@@ -8,13 +8,42 @@ pub struct Rectangle {
 }
 
 impl Rectangle {
+    /// Gets the x coordinate of the upper left corner
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    /// Gets the y coordinate of the upper left corner
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+
+    /// Gets the width
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Gets the height
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
     /// Gets the x coordinate of the right hand border
-    fn right(&self) -> i32 {
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let rectangle = Rectangle::new(10, 20, 30, 40);
+    /// assert_eq!(40, rectangle.right());
+    /// assert_eq!(60, rectangle.bottom());
+    /// ```
+    pub fn right(&self) -> i32 {
         self.x + self.width as i32
     }
 
     /// Gets the y coordinate of the bottom hand border
-    fn bottom(&self) -> i32 {
+    pub fn bottom(&self) -> i32 {
         self.y + self.height as i32
     }
 
@@ -28,10 +57,10 @@ impl Rectangle {
     /// ```
     pub fn new(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
         Rectangle {
-            x: x,
-            y: y,
-            width: width,
-            height: height,
+            x,
+            y,
+            width,
+            height,
         }
     }
 
@@ -59,6 +88,29 @@ impl Rectangle {
         horizontal && vertical
     }
 
+    /// Checks if the given [`Rectangle`] lies completely within this one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let screen = Rectangle::new(0, 0, 1920, 1080);
+    /// assert_eq!(true, screen.contains(&Rectangle::new(0, 0, 1280, 1024)));
+    /// assert_eq!(true, screen.contains(&screen));
+    /// assert_eq!(false, screen.contains(&Rectangle::new(1920, 0, 1280, 1024)));
+    /// ```
+    ///
+    /// # Arguments
+    /// `other` - The [`Rectangle`] to check
+    ///
+    /// # Return value
+    /// `true` if `other` is inside this [`Rectangle`]
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn contains(&self, other: &Rectangle) -> bool {
+        other.x >= self.x && other.right() <= self.right() && other.y >= self.y &&
+        other.bottom() <= self.bottom()
+    }
+
     /// Checks if the [`Rectangle`] overlaps with the given [`Rectangle`],
     /// i.e. if one of the points is within the given [`Rectangle`].
     ///
@@ -72,4 +124,297 @@ impl Rectangle {
         !(other.x >= self.right() || other.right() <= self.x || other.y >= self.bottom() ||
           other.bottom() <= self.y)
     }
+
+    /// Gets the center of the [`Rectangle`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let rectangle = Rectangle::new(10, 10, 20, 11);
+    /// assert_eq!((20, 15), rectangle.center());
+    /// ```
+    ///
+    /// # Return value
+    /// The coordinates of the center, rounded towards the upper left corner
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn center(&self) -> (i32, i32) {
+        (self.x + (self.width / 2) as i32, self.y + (self.height / 2) as i32)
+    }
+
+    /// Computes the distance between the centers of both [`Rectangle`]s
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let a = Rectangle::new(0, 0, 10, 10);
+    /// let b = Rectangle::new(30, 40, 10, 10);
+    /// assert_eq!(50.0, a.distance(&b));
+    /// ```
+    ///
+    /// # Arguments
+    /// `other` - The other [`Rectangle`]
+    ///
+    /// # Return value
+    /// The euclidean distance between both centers
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn distance(&self, other: &Rectangle) -> f64 {
+        let (x1, y1) = self.center();
+        let (x2, y2) = other.center();
+        let (dx, dy) = ((x2 - x1) as f64, (y2 - y1) as f64);
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// Computes the area covered by both [`Rectangle`]s
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let a = Rectangle::new(0, 0, 20, 20);
+    /// let b = Rectangle::new(10, 10, 20, 20);
+    /// assert_eq!(Some(Rectangle::new(10, 10, 10, 10)), a.intersection(&b));
+    /// assert_eq!(None, a.intersection(&Rectangle::new(20, 0, 5, 5)));
+    /// ```
+    ///
+    /// # Arguments
+    /// `other` - The other [`Rectangle`]
+    ///
+    /// # Return value
+    /// The overlapping area or `None` if both [`Rectangle`]s don't overlap
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+        if !self.overlaps(other) {
+            return None;
+        }
+
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        let (right, bottom) = (self.right().min(other.right()), self.bottom().min(other.bottom()));
+        Some(Rectangle::new(x, y, (right - x) as u32, (bottom - y) as u32))
+    }
+
+    /// Computes the smallest [`Rectangle`] containing both [`Rectangle`]s
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let a = Rectangle::new(0, 0, 10, 10);
+    /// let b = Rectangle::new(20, 5, 10, 10);
+    /// assert_eq!(Rectangle::new(0, 0, 30, 15), a.union(&b));
+    /// ```
+    ///
+    /// # Arguments
+    /// `other` - The other [`Rectangle`]
+    ///
+    /// # Return value
+    /// The bounding box of both [`Rectangle`]s
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn union(&self, other: &Rectangle) -> Rectangle {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        let (right, bottom) = (self.right().max(other.right()), self.bottom().max(other.bottom()));
+        Rectangle::new(x, y, (right - x) as u32, (bottom - y) as u32)
+    }
+
+    /// Moves the [`Rectangle`] so that it has the same center
+    /// as the given one, keeping its size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let parent = Rectangle::new(100, 100, 800, 600);
+    /// assert_eq!(Rectangle::new(400, 300, 200, 200),
+    ///            Rectangle::new(0, 0, 200, 200).center_on(&parent));
+    /// assert_eq!(Rectangle::new(50, 300, 900, 200),
+    ///            Rectangle::new(0, 0, 900, 200).center_on(&parent));
+    /// ```
+    ///
+    /// # Arguments
+    /// `other` - The [`Rectangle`] to center on
+    ///
+    /// # Return value
+    /// The moved [`Rectangle`]
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn center_on(&self, other: &Rectangle) -> Rectangle {
+        let x = other.x + (other.width as i32 - self.width as i32) / 2;
+        let y = other.y + (other.height as i32 - self.height as i32) / 2;
+        Rectangle::new(x, y, self.width, self.height)
+    }
+
+    /// Moves the [`Rectangle`] into the given bounds. If it is larger
+    /// than the bounds, it gets shrunk to fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let bounds = Rectangle::new(0, 0, 100, 100);
+    /// assert_eq!(Rectangle::new(80, 0, 20, 20),
+    ///            Rectangle::new(90, -10, 20, 20).clamp(&bounds));
+    /// assert_eq!(Rectangle::new(0, 10, 100, 20),
+    ///            Rectangle::new(-10, 10, 120, 20).clamp(&bounds));
+    /// ```
+    ///
+    /// # Arguments
+    /// `bounds` - The [`Rectangle`] to keep this one inside of
+    ///
+    /// # Return value
+    /// A [`Rectangle`] fully inside `bounds`
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn clamp(&self, bounds: &Rectangle) -> Rectangle {
+        let width = self.width.min(bounds.width);
+        let height = self.height.min(bounds.height);
+        let x = self.x.max(bounds.x).min(bounds.right() - width as i32);
+        let y = self.y.max(bounds.y).min(bounds.bottom() - height as i32);
+        Rectangle::new(x, y, width, height)
+    }
+
+    /// Shrinks the [`Rectangle`] by the given amount on each side,
+    /// e.g. to leave a gap around windows or to make room for a border.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let rectangle = Rectangle::new(0, 0, 100, 50);
+    /// assert_eq!(Rectangle::new(5, 5, 90, 40), rectangle.shrink(5));
+    /// assert_eq!(Rectangle::new(30, 25, 40, 0), rectangle.shrink(30));
+    /// ```
+    ///
+    /// # Arguments
+    /// `amount` - The number of pixels to remove from each side
+    ///
+    /// # Return value
+    /// The shrunk [`Rectangle`], which never gets smaller than zero
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn shrink(&self, amount: u32) -> Rectangle {
+        let dx = amount.min(self.width / 2);
+        let dy = amount.min(self.height / 2);
+        Rectangle::new(self.x + dx as i32,
+                       self.y + dy as i32,
+                       self.width - 2 * dx,
+                       self.height - 2 * dy)
+    }
+
+    /// Splits the [`Rectangle`] into a left and a right part
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let (left, right) = Rectangle::new(0, 0, 101, 50).split_horizontally_by(0.5);
+    /// assert_eq!(Rectangle::new(0, 0, 50, 50), left);
+    /// assert_eq!(Rectangle::new(50, 0, 51, 50), right);
+    /// ```
+    ///
+    /// # Arguments
+    /// `ratio` - The fraction of the width used by the left part
+    ///
+    /// # Return value
+    /// The left and the right part, which together cover the whole [`Rectangle`]
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn split_horizontally_by(&self, ratio: f32) -> (Rectangle, Rectangle) {
+        let width = ((self.width as f32 * ratio.clamp(0.0, 1.0)) as u32).min(self.width);
+        (Rectangle::new(self.x, self.y, width, self.height),
+         Rectangle::new(self.x + width as i32, self.y, self.width - width, self.height))
+    }
+
+    /// Splits the [`Rectangle`] into an upper and a lower part
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let (top, bottom) = Rectangle::new(0, 0, 50, 100).split_vertically_by(0.25);
+    /// assert_eq!(Rectangle::new(0, 0, 50, 25), top);
+    /// assert_eq!(Rectangle::new(0, 25, 50, 75), bottom);
+    /// ```
+    ///
+    /// # Arguments
+    /// `ratio` - The fraction of the height used by the upper part
+    ///
+    /// # Return value
+    /// The upper and the lower part, which together cover the whole [`Rectangle`]
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn split_vertically_by(&self, ratio: f32) -> (Rectangle, Rectangle) {
+        let (top, bottom) = self.transpose().split_horizontally_by(ratio);
+        (top.transpose(), bottom.transpose())
+    }
+
+    /// Splits the [`Rectangle`] into `count` columns of (almost) equal width.
+    /// Pixels that can not be divided evenly are handed out to the
+    /// leftmost columns, so no pixel is lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let columns = Rectangle::new(0, 0, 100, 50).split_horizontally(3);
+    /// assert_eq!(vec![Rectangle::new(0, 0, 34, 50),
+    ///                 Rectangle::new(34, 0, 33, 50),
+    ///                 Rectangle::new(67, 0, 33, 50)],
+    ///            columns);
+    /// ```
+    ///
+    /// # Arguments
+    /// `count` - The number of columns
+    ///
+    /// # Return value
+    /// A vector of `count` columns, from left to right
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn split_horizontally(&self, count: u32) -> Vec<Rectangle> {
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let (width, remainder) = (self.width / count, self.width % count);
+        let mut x = self.x;
+
+        (0..count)
+            .map(|i| {
+                let w = width + if i < remainder { 1 } else { 0 };
+                let column = Rectangle::new(x, self.y, w, self.height);
+                x += w as i32;
+                column
+            })
+            .collect()
+    }
+
+    /// Splits the [`Rectangle`] into `count` rows of (almost) equal height.
+    /// Pixels that can not be divided evenly are handed out to the
+    /// topmost rows, so no pixel is lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let rows = Rectangle::new(0, 10, 50, 11).split_vertically(2);
+    /// assert_eq!(vec![Rectangle::new(0, 10, 50, 6), Rectangle::new(0, 16, 50, 5)], rows);
+    /// ```
+    ///
+    /// # Arguments
+    /// `count` - The number of rows
+    ///
+    /// # Return value
+    /// A vector of `count` rows, from top to bottom
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn split_vertically(&self, count: u32) -> Vec<Rectangle> {
+        self.transpose().split_horizontally(count).into_iter().map(|r| r.transpose()).collect()
+    }
+
+    /// Swaps the x and y axis of the [`Rectangle`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Rectangle;
+    /// let rectangle = Rectangle::new(1, 2, 3, 4);
+    /// assert_eq!(Rectangle::new(2, 1, 4, 3), rectangle.transpose());
+    /// ```
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn transpose(&self) -> Rectangle {
+        Rectangle::new(self.y, self.x, self.height, self.width)
+    }
 }
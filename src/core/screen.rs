@@ -26,7 +26,7 @@ impl<Window: Clone> Clone for Screen<Window> {
     }
 }
 
-impl<Window: Copy + Clone + PartialEq + Eq + Debug> Screen<Window> {
+impl<Window: Copy + Clone + PartialEq + Eq + Debug + 'static> Screen<Window> {
     /// Create a new screen for the given workspace
     /// and the given dimensions
     ///
@@ -49,8 +49,8 @@ impl<Window: Copy + Clone + PartialEq + Eq + Debug> Screen<Window> {
     /// [`Workspace`]: struct.Workspace.html
     pub fn new(workspace: Workspace<Window>, screen_id: u32) -> Screen<Window> {
         Screen {
-            workspace: workspace,
-            screen_id: screen_id,
+            workspace,
+            screen_id,
         }
     }
 
@@ -81,6 +81,17 @@ impl<Window: Copy + Clone + PartialEq + Eq + Debug> Screen<Window> {
         self.workspace.len()
     }
 
+    /// Checks if the [`Screen`]'s [`Workspace`] is empty
+    ///
+    /// # Return value
+    /// `true` if no window is visible on this [`Screen`]
+    ///
+    /// [`Screen`]: struct.Screen.html
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn is_empty(&self) -> bool {
+        self.workspace.is_empty()
+    }
+
     /// Returns a list of all windows visible on
     /// the [`Screen`]'s [`Workspace`]
     ///
@@ -0,0 +1,175 @@
+use core::Rectangle;
+
+/// The point of a [`Rectangle`] a window sticks to when it
+/// gets a different size than the [`Rectangle`]
+///
+/// [`Rectangle`]: struct.Rectangle.html
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Gravity {
+    /// The upper left corner
+    #[default]
+    NorthWest,
+    /// The middle of the upper border
+    North,
+    /// The upper right corner
+    NorthEast,
+    /// The middle of the left border
+    West,
+    /// The center
+    Center,
+    /// The middle of the right border
+    East,
+    /// The lower left corner
+    SouthWest,
+    /// The middle of the lower border
+    South,
+    /// The lower right corner
+    SouthEast,
+}
+
+/// The sizes a window supports, e.g. a terminal that can
+/// only grow by whole character cells or a video player that
+/// keeps its aspect ratio. All sizes are in pixels.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SizeHints {
+    /// The smallest useful width and height
+    pub min_size: Option<(u32, u32)>,
+    /// The largest useful width and height
+    pub max_size: Option<(u32, u32)>,
+    /// The size the increments are added to
+    pub base_size: Option<(u32, u32)>,
+    /// The steps the width and height grow by
+    pub increment: Option<(u32, u32)>,
+    /// The smallest ratio of width to height, as a fraction
+    pub min_aspect: Option<(u32, u32)>,
+    /// The largest ratio of width to height, as a fraction
+    pub max_aspect: Option<(u32, u32)>,
+    /// Where the window wants to stay when it is resized
+    pub gravity: Gravity,
+}
+
+impl SizeHints {
+    /// Checks if the window can't be resized at all
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::SizeHints;
+    /// let hints = SizeHints { min_size: Some((300, 200)), ..SizeHints::default() };
+    /// assert!(!hints.is_fixed());
+    /// let hints = SizeHints { max_size: Some((300, 200)), ..hints };
+    /// assert!(hints.is_fixed());
+    /// ```
+    ///
+    /// # Return value
+    /// `true` if the minimal and the maximal size are the same
+    pub fn is_fixed(&self) -> bool {
+        self.min_size.is_some() && self.min_size == self.max_size
+    }
+
+    /// Computes the largest size up to the given one that the window
+    /// supports. The window never gets larger than the given size,
+    /// not even to reach its minimal size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::SizeHints;
+    /// // A terminal with 8x16 pixel cells and a 2 pixel border
+    /// let terminal = SizeHints {
+    ///     base_size: Some((4, 4)),
+    ///     increment: Some((8, 16)),
+    ///     ..SizeHints::default()
+    /// };
+    /// assert_eq!((804, 404), terminal.constrain(810, 410));
+    ///
+    /// // A video player keeping 16:9
+    /// let player = SizeHints {
+    ///     min_aspect: Some((16, 9)),
+    ///     max_aspect: Some((16, 9)),
+    ///     ..SizeHints::default()
+    /// };
+    /// assert_eq!((960, 540), player.constrain(960, 1000));
+    /// assert_eq!((800, 450), player.constrain(1000, 450));
+    /// ```
+    ///
+    /// # Arguments
+    /// `width` - The available width
+    /// `height` - The available height
+    ///
+    /// # Return value
+    /// The supported width and height
+    pub fn constrain(&self, width: u32, height: u32) -> (u32, u32) {
+        // Like in most window managers, the base size and the
+        // minimal size stand in for each other if one is missing
+        let (base_width, base_height) = self.base_size.or(self.min_size).unwrap_or((0, 0));
+        let (min_width, min_height) = self.min_size.or(self.base_size).unwrap_or((0, 0));
+        let mut w = width.saturating_sub(base_width) as f64;
+        let mut h = height.saturating_sub(base_height) as f64;
+
+        if let (Some((min_x, min_y)), Some((max_x, max_y))) = (self.min_aspect, self.max_aspect) {
+            if min_x > 0 && min_y > 0 && max_x > 0 && max_y > 0 && w > 0.0 && h > 0.0 {
+                let (min, max) = (min_x as f64 / min_y as f64, max_x as f64 / max_y as f64);
+                if w / h > max {
+                    w = (h * max).round();
+                } else if w / h < min {
+                    h = (w / min).round();
+                }
+            }
+        }
+
+        let (mut w, mut h) = (w as u32, h as u32);
+        if let Some((x, y)) = self.increment {
+            w -= w.checked_rem(x).unwrap_or(0);
+            h -= h.checked_rem(y).unwrap_or(0);
+        }
+
+        let (mut w, mut h) = ((w + base_width).max(min_width), (h + base_height).max(min_height));
+        if let Some((max_width, max_height)) = self.max_size {
+            if max_width > 0 {
+                w = w.min(max_width);
+            }
+            if max_height > 0 {
+                h = h.min(max_height);
+            }
+        }
+        (w.min(width), h.min(height))
+    }
+
+    /// Fits the window into the given [`Rectangle`], placing
+    /// it at the point given by the [`Gravity`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::{Gravity, Rectangle, SizeHints};
+    /// let hints = SizeHints { max_size: Some((400, 300)), ..SizeHints::default() };
+    /// let slot = Rectangle::new(0, 0, 800, 600);
+    /// assert_eq!(Rectangle::new(200, 150, 400, 300), hints.apply(&slot, Gravity::Center));
+    /// assert_eq!(Rectangle::new(400, 300, 400, 300), hints.apply(&slot, Gravity::SouthEast));
+    /// ```
+    ///
+    /// # Arguments
+    /// `area` - The [`Rectangle`] available to the window
+    /// `gravity` - Where to place the window inside `area`
+    ///
+    /// # Return value
+    /// The [`Rectangle`] of the window, inside `area`
+    /// [`Gravity`]: enum.Gravity.html
+    /// [`Rectangle`]: struct.Rectangle.html
+    pub fn apply(&self, area: &Rectangle, gravity: Gravity) -> Rectangle {
+        let (width, height) = self.constrain(area.width(), area.height());
+        let (dx, dy) = (area.width() - width, area.height() - height);
+        let x = match gravity {
+            Gravity::NorthWest | Gravity::West | Gravity::SouthWest => 0,
+            Gravity::North | Gravity::Center | Gravity::South => dx / 2,
+            Gravity::NorthEast | Gravity::East | Gravity::SouthEast => dx,
+        };
+        let y = match gravity {
+            Gravity::NorthWest | Gravity::North | Gravity::NorthEast => 0,
+            Gravity::West | Gravity::Center | Gravity::East => dy / 2,
+            Gravity::SouthWest | Gravity::South | Gravity::SouthEast => dy,
+        };
+        Rectangle::new(area.x() + x as i32, area.y() + y as i32, width, height)
+    }
+}
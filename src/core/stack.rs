@@ -15,11 +15,11 @@ use std::fmt::Debug;
 /// [`Stack`]: struct.Stack.html
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Stack<T> {
-    ///
+    /// The currently focused element
     pub focus: T,
-    ///
+    /// All elements above the focused one, closest first
     pub up: Vec<T>,
-    ///
+    /// All elements below the focused one, closest first
     pub down: Vec<T>,
 }
 
@@ -83,7 +83,7 @@ impl<T: Debug + Copy + Clone + Eq> Stack<T> {
             down: self.down
                 .clone()
                 .into_iter()
-                .chain((vec![self.focus]).into_iter())
+                .chain([self.focus].iter().cloned())
                 .collect(),
         }
     }
@@ -110,7 +110,7 @@ impl<T: Debug + Copy + Clone + Eq> Stack<T> {
         self.up
             .iter()
             .rev()
-            .chain(vec![self.focus].iter())
+            .chain([self.focus].iter())
             .chain(self.down.iter())
             .cloned()
             .collect()
@@ -141,7 +141,7 @@ impl<T: Debug + Copy + Clone + Eq> Stack<T> {
         where F: Fn(&T) -> bool
     {
         trace!("filtering stack");
-        let lrs: Vec<T> = (vec![self.focus])
+        let lrs: Vec<T> = [self.focus]
             .iter()
             .chain(self.down.iter())
             .filter(|&x| f(x))
@@ -180,7 +180,7 @@ impl<T: Debug + Copy + Clone + Eq> Stack<T> {
         if self.up.is_empty() {
             let tmp: Vec<T> = (vec![self.focus])
                 .into_iter()
-                .chain(self.down.clone().into_iter())
+                .chain(self.down.clone())
                 .rev()
                 .collect();
             let xs: Vec<T> = tmp.iter()
@@ -192,7 +192,7 @@ impl<T: Debug + Copy + Clone + Eq> Stack<T> {
         } else {
             let down: Vec<T> = (vec![self.focus])
                 .into_iter()
-                .chain(self.down.clone().into_iter())
+                .chain(self.down.clone())
                 .collect();
             let up = self.up.iter().skip(1).cloned().collect();
             Stack::<T>::new(self.up[0], up, down)
@@ -215,7 +215,7 @@ impl<T: Debug + Copy + Clone + Eq> Stack<T> {
         } else {
             let x = self.up[0];
             let xs: Vec<T> = self.up.iter().skip(1).cloned().collect();
-            let rs: Vec<T> = (vec![x]).into_iter().chain(self.down.clone().into_iter()).collect();
+            let rs: Vec<T> = [x].iter().cloned().chain(self.down.clone()).collect();
             Stack::<T>::new(self.focus, xs, rs)
         }
     }
@@ -244,8 +244,8 @@ impl<T: Debug + Copy + Clone + Eq> Stack<T> {
             .cloned()
             .collect();
         let rs: Vec<T> = xs.into_iter()
-            .chain((vec![x]).into_iter())
-            .chain(self.down.clone().into_iter())
+            .chain([x].iter().cloned())
+            .chain(self.down.clone())
             .collect();
 
         Stack::<T>::new(self.focus, Vec::new(), rs)
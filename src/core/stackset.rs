@@ -0,0 +1,565 @@
+use core::rectangle::Rectangle;
+use core::screen::Screen;
+use core::stack::Stack;
+use core::workspace::Workspace;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// The [`StackSet`] is the single authoritative model of the
+/// window manager's state. It ties all [`Screen`]s and
+/// [`Workspace`]s together:
+///
+/// * `current` is the [`Screen`] that has the focus
+/// * `visible` are all other [`Screen`]s, i.e. all other workspaces
+///   that are shown on some monitor
+/// * `hidden` are all [`Workspace`]s that are not shown at all
+/// * `floating` maps windows that are not tiled to their geometry
+///
+/// # Immutable
+///
+/// Note that this [`StackSet`] implementation is immutable
+/// and that each operation that would modify it, instead
+/// returns a new copy of the [`StackSet`] with the modified state.
+///
+/// [`StackSet`]: struct.StackSet.html
+/// [`Screen`]: struct.Screen.html
+/// [`Workspace`]: struct.Workspace.html
+pub struct StackSet<Window> {
+    /// The currently focused [`Screen`]
+    /// [`Screen`]: struct.Screen.html
+    pub current: Screen<Window>,
+    /// All other visible [`Screen`]s
+    /// [`Screen`]: struct.Screen.html
+    pub visible: Vec<Screen<Window>>,
+    /// All [`Workspace`]s that are currently not shown
+    /// [`Workspace`]: struct.Workspace.html
+    pub hidden: Vec<Workspace<Window>>,
+    /// Floating windows and their geometry
+    pub floating: HashMap<Window, Rectangle>,
+}
+
+impl<Window: Clone + Eq + Hash> Clone for StackSet<Window> {
+    fn clone(&self) -> StackSet<Window> {
+        StackSet {
+            current: self.current.clone(),
+            visible: self.visible.clone(),
+            hidden: self.hidden.clone(),
+            floating: self.floating.clone(),
+        }
+    }
+}
+
+impl<Window: Copy + Clone + PartialEq + Eq + Debug + Hash + 'static> StackSet<Window> {
+    /// Create a new [`StackSet`] with one empty [`Workspace`] per tag.
+    /// The first `screens` workspaces are shown on the screens,
+    /// the rest of them is hidden.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::StackSet;
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2", "3"], 2);
+    /// assert_eq!(0, stack_set.current.workspace.id);
+    /// assert_eq!(1, stack_set.visible.len());
+    /// assert_eq!(1, stack_set.hidden.len());
+    /// ```
+    ///
+    /// # Arguments
+    /// `tags` - The names of the [`Workspace`]s to create
+    /// `screens` - The number of screens
+    ///
+    /// # Return value
+    /// A new [`StackSet`] with empty [`Workspace`]s
+    ///
+    /// # Panics
+    /// If there are no tags or fewer tags than screens
+    ///
+    /// [`StackSet`]: struct.StackSet.html
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn new<S: Into<String>>(tags: Vec<S>, screens: u32) -> StackSet<Window> {
+        let screens = screens.max(1);
+        assert!(tags.len() >= screens as usize,
+                "need at least as many workspaces as screens");
+
+        let mut workspaces = tags.into_iter()
+            .enumerate()
+            .map(|(id, tag)| Workspace::new(id as u32, tag, None));
+        let mut screens = workspaces.by_ref()
+            .take(screens as usize)
+            .enumerate()
+            .map(|(id, workspace)| Screen::new(workspace, id as u32));
+
+        trace!("creating new stack set");
+        StackSet {
+            current: screens.next().unwrap(),
+            visible: screens.collect(),
+            hidden: workspaces.collect(),
+            floating: HashMap::new(),
+        }
+    }
+
+    /// Returns all [`Screen`]s, starting with the current one
+    ///
+    /// [`Screen`]: struct.Screen.html
+    pub fn screens(&self) -> Vec<&Screen<Window>> {
+        Some(&self.current).into_iter().chain(self.visible.iter()).collect()
+    }
+
+    /// Returns all [`Workspace`]s, starting with the current one,
+    /// followed by the visible and the hidden ones.
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn workspaces(&self) -> Vec<&Workspace<Window>> {
+        self.screens()
+            .into_iter()
+            .map(|s| &s.workspace)
+            .chain(self.hidden.iter())
+            .collect()
+    }
+
+    /// Returns the [`Workspace`] with the given id, if it exists
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn workspace(&self, id: u32) -> Option<&Workspace<Window>> {
+        self.workspaces().into_iter().find(|w| w.id == id)
+    }
+
+    /// Returns the focused window of the current [`Workspace`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::StackSet;
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2"], 1);
+    /// assert_eq!(None, stack_set.peek());
+    /// assert_eq!(Some(42), stack_set.insert(42).peek());
+    /// ```
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn peek(&self) -> Option<Window> {
+        self.current.workspace.peek()
+    }
+
+    /// Returns all windows of the current [`Workspace`]
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn index(&self) -> Vec<Window> {
+        self.current.windows()
+    }
+
+    /// Returns all windows managed by this [`StackSet`]
+    ///
+    /// [`StackSet`]: struct.StackSet.html
+    pub fn all_windows(&self) -> Vec<Window> {
+        self.workspaces().into_iter().flat_map(|w| w.windows()).collect()
+    }
+
+    /// Checks if the given window is managed by any [`Workspace`]
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn contains(&self, window: Window) -> bool {
+        self.find_workspace(window).is_some()
+    }
+
+    /// Checks if the given window is floating
+    pub fn is_floating(&self, window: Window) -> bool {
+        self.floating.contains_key(&window)
+    }
+
+    /// Find the [`Workspace`] that contains the given window
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::StackSet;
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2"], 1);
+    /// let stack_set = stack_set.insert(42).shift(1);
+    /// assert_eq!(Some(1), stack_set.find_workspace(42));
+    /// assert_eq!(None, stack_set.find_workspace(23));
+    /// ```
+    ///
+    /// # Return value
+    /// The id of the [`Workspace`] or `None` if the window is not managed
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn find_workspace(&self, window: Window) -> Option<u32> {
+        self.workspaces().into_iter().find(|w| w.contains(window)).map(|w| w.id)
+    }
+
+    /// Find the [`Screen`] the given window is shown on
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::StackSet;
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2", "3"], 2);
+    /// let stack_set = stack_set.insert(42).shift(1).insert(23).shift(2);
+    /// assert_eq!(Some(1), stack_set.screen_of(42));
+    /// assert_eq!(None, stack_set.screen_of(23));
+    /// ```
+    ///
+    /// # Return value
+    /// The id of the [`Screen`] or `None` if the window is not visible
+    ///
+    /// [`Screen`]: struct.Screen.html
+    pub fn screen_of(&self, window: Window) -> Option<u32> {
+        self.screens().into_iter().find(|s| s.contains(window)).map(|s| s.screen_id)
+    }
+
+    /// Returns the id of the [`Workspace`] shown on the given [`Screen`]
+    ///
+    /// [`Screen`]: struct.Screen.html
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn lookup_workspace(&self, screen_id: u32) -> Option<u32> {
+        self.screens()
+            .into_iter()
+            .find(|s| s.screen_id == screen_id)
+            .map(|s| s.workspace.id)
+    }
+
+    /// Switch the current [`Screen`] to the given [`Workspace`].
+    /// If the [`Workspace`] is already visible on another [`Screen`],
+    /// that [`Screen`] becomes the current one instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::StackSet;
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2", "3"], 2);
+    ///
+    /// let hidden = stack_set.view(2);
+    /// assert_eq!((0, 2), (hidden.current.screen_id, hidden.current.workspace.id));
+    ///
+    /// let visible = stack_set.view(1);
+    /// assert_eq!((1, 1), (visible.current.screen_id, visible.current.workspace.id));
+    /// ```
+    ///
+    /// # Arguments
+    /// `id` - The id of the [`Workspace`] to show
+    ///
+    /// # Return value
+    /// A new [`StackSet`] with the [`Workspace`] in focus
+    ///
+    /// [`StackSet`]: struct.StackSet.html
+    /// [`Screen`]: struct.Screen.html
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn view(&self, id: u32) -> StackSet<Window> {
+        trace!("viewing workspace {}", id);
+        if self.current.workspace.id == id {
+            return self.clone();
+        }
+
+        if let Some(index) = self.visible.iter().position(|s| s.workspace.id == id) {
+            let mut visible = self.visible.clone();
+            let screen = visible.remove(index);
+            visible.insert(0, self.current.clone());
+            return StackSet { current: screen, visible, ..self.clone() };
+        }
+
+        if let Some(index) = self.hidden.iter().position(|w| w.id == id) {
+            let mut hidden = self.hidden.clone();
+            let workspace = hidden.remove(index);
+            hidden.insert(0, self.current.workspace.clone());
+            let current = Screen::new(workspace, self.current.screen_id);
+            return StackSet { current, hidden, ..self.clone() };
+        }
+
+        self.clone()
+    }
+
+    /// Like [`view`], but instead of moving the focus to another [`Screen`]
+    /// showing the [`Workspace`], swap the [`Workspace`]s of the current
+    /// and that [`Screen`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::StackSet;
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2", "3"], 2);
+    /// let stack_set = stack_set.greedy_view(1);
+    /// assert_eq!((0, 1), (stack_set.current.screen_id, stack_set.current.workspace.id));
+    /// assert_eq!(Some(0), stack_set.lookup_workspace(1));
+    /// ```
+    ///
+    /// [`view`]: struct.StackSet.html#method.view
+    /// [`Screen`]: struct.Screen.html
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn greedy_view(&self, id: u32) -> StackSet<Window> {
+        trace!("greedily viewing workspace {}", id);
+        match self.visible.iter().position(|s| s.workspace.id == id) {
+            Some(index) => {
+                let mut visible = self.visible.clone();
+                let current = Screen::new(visible[index].workspace.clone(),
+                                          self.current.screen_id);
+                visible[index] = Screen::new(self.current.workspace.clone(),
+                                             visible[index].screen_id);
+                StackSet { current, visible, ..self.clone() }
+            }
+            None => self.view(id),
+        }
+    }
+
+    /// Focus the [`Screen`] with the given id
+    ///
+    /// [`Screen`]: struct.Screen.html
+    pub fn view_screen(&self, screen_id: u32) -> StackSet<Window> {
+        self.lookup_workspace(screen_id).map_or_else(|| self.clone(), |id| self.view(id))
+    }
+
+    /// Rebuild the [`Screen`]s for the given number of screens.
+    /// All [`Workspace`]s keep their order, the first `screens` of them
+    /// become visible, the rest is hidden.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::StackSet;
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2", "3"], 1);
+    /// let stack_set = stack_set.rescreen(2);
+    /// assert_eq!(2, stack_set.screens().len());
+    /// assert_eq!(1, stack_set.hidden.len());
+    /// ```
+    ///
+    /// [`Screen`]: struct.Screen.html
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn rescreen(&self, screens: u32) -> StackSet<Window> {
+        let mut ordered = self.screens();
+        ordered.sort_by_key(|s| s.screen_id);
+        let workspaces: Vec<Workspace<Window>> = ordered.into_iter()
+            .map(|s| s.workspace.clone())
+            .chain(self.hidden.iter().cloned())
+            .collect();
+        let screens = (screens.max(1) as usize).min(workspaces.len());
+        let current_id = self.current.workspace.id;
+
+        let mut workspaces = workspaces.into_iter();
+        let mut screens: Vec<Screen<Window>> = workspaces.by_ref()
+            .take(screens)
+            .enumerate()
+            .map(|(id, workspace)| Screen::new(workspace, id as u32))
+            .collect();
+        let current = screens.remove(0);
+
+        let stack_set = StackSet {
+            current,
+            visible: screens,
+            hidden: workspaces.collect(),
+            floating: self.floating.clone(),
+        };
+
+        // Keep the focus on the same workspace if it is still visible
+        if stack_set.visible.iter().any(|s| s.workspace.id == current_id) {
+            stack_set.view(current_id)
+        } else {
+            stack_set
+        }
+    }
+
+    /// Apply the given function to the current [`Workspace`]
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn map_workspace<F>(&self, f: F) -> StackSet<Window>
+        where F: Fn(Workspace<Window>) -> Workspace<Window>
+    {
+        StackSet { current: self.current.map_workspace(f), ..self.clone() }
+    }
+
+    /// Apply the given function to the [`Workspace`] with the given id
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn map_workspace_by_id<F>(&self, id: u32, f: F) -> StackSet<Window>
+        where F: Fn(Workspace<Window>) -> Workspace<Window>
+    {
+        let map_screen = |s: &Screen<Window>| if s.workspace.id == id {
+            s.map_workspace(&f)
+        } else {
+            s.clone()
+        };
+
+        StackSet {
+            current: map_screen(&self.current),
+            visible: self.visible.iter().map(&map_screen).collect(),
+            hidden: self.hidden
+                .iter()
+                .map(|w| if w.id == id { f(w.clone()) } else { w.clone() })
+                .collect(),
+            floating: self.floating.clone(),
+        }
+    }
+
+    /// Apply the given function to the [`Stack`] of the current [`Workspace`]
+    ///
+    /// [`Stack`]: struct.Stack.html
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn map<F>(&self, f: F) -> StackSet<Window>
+        where F: Fn(Stack<Window>) -> Stack<Window>
+    {
+        StackSet { current: self.current.map(f), ..self.clone() }
+    }
+
+    /// Move the focus up in the current [`Workspace`]
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn focus_up(&self) -> StackSet<Window> {
+        self.map(|s| s.focus_up())
+    }
+
+    /// Move the focus down in the current [`Workspace`]
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn focus_down(&self) -> StackSet<Window> {
+        self.map(|s| s.focus_down())
+    }
+
+    /// Swap the focused window with the one above it
+    pub fn swap_up(&self) -> StackSet<Window> {
+        self.map(|s| s.swap_up())
+    }
+
+    /// Swap the focused window with the one below it
+    pub fn swap_down(&self) -> StackSet<Window> {
+        self.map(|s| s.swap_down())
+    }
+
+    /// Swap the focused window with the master window
+    pub fn swap_master(&self) -> StackSet<Window> {
+        self.map(|s| s.swap_master())
+    }
+
+    /// Move the focus to the given window. If the window is on
+    /// another [`Workspace`], that [`Workspace`] is viewed first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::StackSet;
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2"], 1);
+    /// let stack_set = stack_set.insert(1).insert(2).insert(3);
+    /// assert_eq!(Some(1), stack_set.focus_window(1).peek());
+    /// ```
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn focus_window(&self, window: Window) -> StackSet<Window> {
+        if self.peek() == Some(window) {
+            return self.clone();
+        }
+
+        match self.find_workspace(window) {
+            Some(id) => {
+                self.view(id).map(|s| {
+                    let mut stack = s;
+                    for _ in 0..stack.len() {
+                        if stack.focus == window {
+                            break;
+                        }
+                        stack = stack.focus_down();
+                    }
+                    stack
+                })
+            }
+            None => self.clone(),
+        }
+    }
+
+    /// Insert a new window into the current [`Workspace`] and focus it.
+    /// Windows that are already managed are left alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::StackSet;
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2"], 1);
+    /// let stack_set = stack_set.insert(42).insert(42);
+    /// assert_eq!(vec![42], stack_set.index());
+    /// ```
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn insert(&self, window: Window) -> StackSet<Window> {
+        if self.contains(window) {
+            return self.clone();
+        }
+
+        trace!("inserting window {:?} into stack set", window);
+        self.map_workspace(|w| w.add(window))
+    }
+
+    /// Remove the given window from all [`Workspace`]s
+    /// and from the floating layer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::{Rectangle, StackSet};
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2"], 1);
+    /// let stack_set = stack_set.insert(42).float(42, Rectangle::new(0, 0, 10, 10));
+    /// let stack_set = stack_set.delete(42);
+    /// assert_eq!(false, stack_set.contains(42));
+    /// assert_eq!(false, stack_set.is_floating(42));
+    /// ```
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn delete(&self, window: Window) -> StackSet<Window> {
+        trace!("deleting window {:?} from stack set", window);
+        let stack_set = self.sink(window);
+        StackSet {
+            current: stack_set.current.map_workspace(|w| w.remove(window)),
+            visible: stack_set.visible
+                .iter()
+                .map(|s| s.map_workspace(|w| w.remove(window)))
+                .collect(),
+            hidden: stack_set.hidden.iter().map(|w| w.remove(window)).collect(),
+            floating: stack_set.floating,
+        }
+    }
+
+    /// Make the given window float with the given geometry
+    pub fn float(&self, window: Window, rectangle: Rectangle) -> StackSet<Window> {
+        let mut floating = self.floating.clone();
+        floating.insert(window, rectangle);
+        StackSet { floating, ..self.clone() }
+    }
+
+    /// Put the given window back into the tiling layer
+    pub fn sink(&self, window: Window) -> StackSet<Window> {
+        let mut floating = self.floating.clone();
+        floating.remove(&window);
+        StackSet { floating, ..self.clone() }
+    }
+
+    /// Move the focused window to the [`Workspace`] with the given id.
+    /// The focus stays on the current [`Workspace`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::StackSet;
+    /// let stack_set : StackSet<u32> = StackSet::new(vec!["1", "2"], 1);
+    /// let stack_set = stack_set.insert(42).shift(1);
+    /// assert_eq!(None, stack_set.peek());
+    /// assert_eq!(vec![42], stack_set.workspace(1).unwrap().windows());
+    /// ```
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn shift(&self, id: u32) -> StackSet<Window> {
+        match self.peek() {
+            Some(window) => self.shift_window(id, window),
+            None => self.clone(),
+        }
+    }
+
+    /// Move the given window to the [`Workspace`] with the given id.
+    ///
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn shift_window(&self, id: u32, window: Window) -> StackSet<Window> {
+        match self.find_workspace(window) {
+            Some(from) if from != id && self.workspace(id).is_some() => {
+                trace!("shifting window {:?} from workspace {} to {}", window, from, id);
+                let floating = self.floating.clone();
+                let stack_set = self.delete(window).map_workspace_by_id(id, |w| w.add(window));
+                StackSet { floating, ..stack_set }
+            }
+            _ => self.clone(),
+        }
+    }
+}
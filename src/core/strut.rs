@@ -0,0 +1,106 @@
+use core::Rectangle;
+
+/// The edge of the screen a [`Strut`] is attached to
+///
+/// [`Strut`]: struct.Strut.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Edge {
+    /// The left edge, space is taken from the left
+    Left,
+    /// The right edge, space is taken from the right
+    Right,
+    /// The top edge, space is taken from the top
+    Top,
+    /// The bottom edge, space is taken from the bottom
+    Bottom,
+}
+
+/// Space reserved by a panel or dock along one edge
+/// of the screen, which windows should not cover.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Strut {
+    /// The [`Edge`] the space is reserved at
+    /// [`Edge`]: enum.Edge.html
+    pub edge: Edge,
+    /// The reserved area in root window coordinates
+    pub area: Rectangle,
+}
+
+impl Strut {
+    /// Create a new [`Strut`] reserving the given area
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::{Edge, Rectangle, Strut};
+    /// let strut = Strut::new(Edge::Top, Rectangle::new(0, 0, 1920, 20));
+    /// ```
+    ///
+    /// # Arguments
+    /// `edge` - The [`Edge`] the space is reserved at
+    /// `area` - The reserved area in root window coordinates
+    ///
+    /// [`Edge`]: enum.Edge.html
+    /// [`Strut`]: struct.Strut.html
+    pub fn new(edge: Edge, area: Rectangle) -> Strut {
+        Strut { edge, area }
+    }
+
+    /// Removes the reserved area from the given screen, if it
+    /// lies on the screen. Only the part of the screen between the
+    /// reserved area and the opposite edge remains.
+    ///
+    /// Struts spanning the whole screen from one side to the other
+    /// are ignored. With several monitors, they belong to the edge
+    /// of the whole display, but touch a screen further inside.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::{Edge, Rectangle, Strut};
+    /// let left = Rectangle::new(0, 0, 1920, 1080);
+    /// let right = Rectangle::new(1920, 0, 1280, 1024);
+    ///
+    /// // A panel at the top of the left monitor
+    /// let top = Strut::new(Edge::Top, Rectangle::new(0, 0, 1920, 20));
+    /// assert_eq!(Rectangle::new(0, 20, 1920, 1060), top.apply(&left));
+    /// assert_eq!(right, top.apply(&right));
+    ///
+    /// // A dock at the right edge of the whole display
+    /// let dock = Strut::new(Edge::Right, Rectangle::new(3150, 0, 50, 1024));
+    /// assert_eq!(left, dock.apply(&left));
+    /// assert_eq!(Rectangle::new(1920, 0, 1230, 1024), dock.apply(&right));
+    /// ```
+    ///
+    /// # Arguments
+    /// `screen` - The area of the screen
+    ///
+    /// # Return value
+    /// The area of the screen that is left for windows
+    pub fn apply(&self, screen: &Rectangle) -> Rectangle {
+        let area = match self.area.intersection(screen) {
+            Some(area) => area,
+            None => return *screen,
+        };
+
+        let (x, y) = (screen.x(), screen.y());
+        let (width, height) = (screen.width(), screen.height());
+        match self.edge {
+            Edge::Left if area.width() < width => {
+                let right = area.right();
+                Rectangle::new(right, y, (screen.right() - right) as u32, height)
+            }
+            Edge::Right if area.width() < width => {
+                Rectangle::new(x, y, (area.x() - x) as u32, height)
+            }
+            Edge::Top if area.height() < height => {
+                let bottom = area.bottom();
+                Rectangle::new(x, bottom, width, (screen.bottom() - bottom) as u32)
+            }
+            Edge::Bottom if area.height() < height => {
+                Rectangle::new(x, y, width, (area.y() - y) as u32)
+            }
+            _ => *screen,
+        }
+    }
+}
@@ -1,5 +1,5 @@
-// use layout::{Layout, LayoutMessage};
 use core::Stack;
+use layout::{self, Layout, LayoutMessage};
 use std::fmt::Debug;
 
 /// Represents a single workspace with a `tag` (name),
@@ -18,12 +18,15 @@ use std::fmt::Debug;
 ///
 /// [`Workspace`]: struct.Stack.html
 pub struct Workspace<Window> {
-    ///
+    /// The [`Workspace`]'s unique identifier
     pub id: u32,
-    ///
+    /// The [`Workspace`]'s name as shown to the user
     pub tag: String,
-    ///
+    /// All windows on this [`Workspace`], if there are any
     pub stack: Option<Stack<Window>>,
+    /// The [`Layout`] used to arrange the windows
+    /// [`Layout`]: ../layout/trait.Layout.html
+    pub layout: Box<dyn Layout<Window>>,
 }
 
 impl<Window: Clone> Clone for Workspace<Window> {
@@ -32,12 +35,13 @@ impl<Window: Clone> Clone for Workspace<Window> {
             id: self.id,
             tag: self.tag.clone(),
             stack: self.stack.clone(),
+            layout: self.layout.copy(),
         }
     }
 }
 
-impl<Window: Copy + Clone + PartialEq + Eq + Debug> Workspace<Window> {
-    /// Create a new workspace
+impl<Window: Copy + Clone + PartialEq + Eq + Debug + 'static> Workspace<Window> {
+    /// Create a new workspace with the default [`Layout`]
     ///
     /// # Examples
     ///
@@ -47,6 +51,8 @@ impl<Window: Copy + Clone + PartialEq + Eq + Debug> Workspace<Window> {
     /// let workspace = Workspace::new(0, "Desktop 0", Some(stack));
     /// assert_eq!(1, workspace.len());
     /// ```
+    ///
+    /// [`Layout`]: ../layout/trait.Layout.html
     pub fn new<S: Into<String>>(id: u32,
                                 tag: S,
                                 stack: Option<Stack<Window>>)
@@ -54,9 +60,67 @@ impl<Window: Copy + Clone + PartialEq + Eq + Debug> Workspace<Window> {
         let tag = tag.into();
         trace!("workspace_tag" => tag, "workspace_id" => id; "creating new workspace");
         Workspace {
-            id: id,
-            tag: tag,
-            stack: stack,
+            id,
+            tag,
+            stack,
+            layout: layout::default_layout(),
+        }
+    }
+
+    /// Replace the [`Stack`] of the [`Workspace`], keeping everything else
+    ///
+    /// [`Stack`]: struct.Stack.html
+    /// [`Workspace`]: struct.Workspace.html
+    fn with_stack(&self, stack: Option<Stack<Window>>) -> Workspace<Window> {
+        Workspace { stack, ..self.clone() }
+    }
+
+    /// Use the given [`Layout`] for this [`Workspace`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sabiwm::core::Workspace;
+    /// # use sabiwm::layout::{Choose, Full, Tall};
+    /// let workspace : Workspace<u32> = Workspace::new(0, "Desktop 0", None);
+    /// let layout = Choose::new(vec![Box::new(Full), Box::new(Tall::default())]);
+    /// let workspace = workspace.with_layout(Box::new(layout));
+    /// assert_eq!("Full", workspace.layout.name());
+    /// ```
+    ///
+    /// # Arguments
+    /// `layout` - The new [`Layout`]
+    ///
+    /// # Return value
+    /// A new [`Workspace`] arranged by the given [`Layout`]
+    ///
+    /// [`Layout`]: ../layout/trait.Layout.html
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn with_layout(&self, layout: Box<dyn Layout<Window>>) -> Workspace<Window> {
+        Workspace {
+            id: self.id,
+            tag: self.tag.clone(),
+            stack: self.stack.clone(),
+            layout,
+        }
+    }
+
+    /// Send the given [`LayoutMessage`] to the [`Workspace`]'s [`Layout`]
+    ///
+    /// # Arguments
+    /// `message` - The [`LayoutMessage`] to send
+    ///
+    /// # Return value
+    /// A new [`Workspace`] with the [`Layout`] after handling the message
+    ///
+    /// [`Layout`]: ../layout/trait.Layout.html
+    /// [`LayoutMessage`]: ../layout/enum.LayoutMessage.html
+    /// [`Workspace`]: struct.Workspace.html
+    pub fn send_message(&self, message: LayoutMessage) -> Workspace<Window> {
+        trace!("workspace_tag" => self.tag, "workspace_id" => self.id; "sending {:?} to layout", message);
+        match self.layout.handle_message(message) {
+            Some(layout) => self.with_layout(layout),
+            None => self.clone(),
         }
     }
 
@@ -72,11 +136,9 @@ impl<Window: Copy + Clone + PartialEq + Eq + Debug> Workspace<Window> {
     /// ```
     pub fn add(&self, window: Window) -> Workspace<Window> {
         trace!("workspace_tag" => self.tag, "workspace_id" => self.id; "adding window {:?} to workspace", window);
-        Workspace::new(self.id,
-                       self.tag.clone(),
-                       Some(self.stack
-                           .clone()
-                           .map_or(Stack::from(window), |s| s.add(window))))
+        self.with_stack(Some(self.stack
+            .clone()
+            .map_or(Stack::from(window), |s| s.add(window))))
     }
 
     /// Remove the given window from the workspace.
@@ -90,9 +152,7 @@ impl<Window: Copy + Clone + PartialEq + Eq + Debug> Workspace<Window> {
     /// [`Workspace`]: struct.Workspace.html
     pub fn remove(&self, window: Window) -> Workspace<Window> {
         trace!("workspace_tag" => self.tag, "workspace_id" => self.id; "removing window {:?} from workspace", window);
-        Workspace::new(self.id,
-                       self.tag.clone(),
-                       self.stack.clone().map_or(None, |s| s.filter(|&w| w != window)))
+        self.with_stack(self.stack.clone().and_then(|s| s.filter(|&w| w != window)))
     }
 
     /// Returns the number of windows contained in this [`Workspace`]
@@ -118,7 +178,7 @@ impl<Window: Copy + Clone + PartialEq + Eq + Debug> Workspace<Window> {
     /// [`Workspace`]: struct.Workspace.html
     pub fn contains(&self, window: Window) -> bool {
         trace!("workspace_tag" => self.tag, "workspace_id" => self.id; "checking if workspace contains window {:?}", window);
-        self.stack.clone().map_or(false, |x| x.contains(window))
+        self.stack.clone().is_some_and(|x| x.contains(window))
     }
 
     /// [`Workspace`]: struct.Workspace.html
@@ -136,7 +196,7 @@ impl<Window: Copy + Clone + PartialEq + Eq + Debug> Workspace<Window> {
         where F: Fn(Stack<Window>) -> Stack<Window>
     {
         trace!("workspace_tag" => self.tag, "workspace_id" => self.id; "mapping over workspace");
-        Workspace::new(self.id, self.tag.clone(), self.stack.clone().map(f))
+        self.with_stack(self.stack.clone().map(f))
     }
 
     /// [`Workspace`]: struct.Workspace.html
@@ -144,9 +204,7 @@ impl<Window: Copy + Clone + PartialEq + Eq + Debug> Workspace<Window> {
         where F: Fn(Stack<Window>) -> Option<Stack<Window>>
     {
         trace!("workspace_tag" => self.tag, "workspace_id" => self.id; "mapping optional over workspace");
-        Workspace::new(self.id,
-                       self.tag.clone(),
-                       self.stack.clone().map_or(None, f))
+        self.with_stack(self.stack.clone().and_then(f))
     }
 
     /// [`Workspace`]: struct.Workspace.html
@@ -154,8 +212,6 @@ impl<Window: Copy + Clone + PartialEq + Eq + Debug> Workspace<Window> {
         where F: Fn(Stack<Window>) -> Stack<Window>
     {
         trace!("workspace_tag" => self.tag, "workspace_id" => self.id; "mapping default over workspace");
-        Workspace::new(self.id,
-                       self.tag.clone(),
-                       Some(self.stack.clone().map_or(default, f)))
+        self.with_stack(Some(self.stack.clone().map_or(default, f)))
     }
 }
@@ -0,0 +1,53 @@
+//! The connecting side of the IPC socket, as used by `sabictl`.
+
+use errors::*;
+use ipc::{Reply, Request};
+use serde_json;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+/// A connection to a running window manager
+pub struct Client {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Client {
+    /// Connect to the window manager listening on the given socket
+    pub fn connect(path: &Path) -> Result<Client> {
+        let writer = UnixStream::connect(path)
+            .chain_err(|| format!("unable to connect to {}", path.display()))?;
+        let reader = writer.try_clone().chain_err(|| "unable to set up connection")?;
+        let reader = BufReader::new(reader);
+        Ok(Client { reader, writer })
+    }
+
+    /// Send the given [`Request`] and wait for the [`Reply`]
+    ///
+    /// [`Reply`]: enum.Reply.html
+    /// [`Request`]: enum.Request.html
+    pub fn request(&mut self, request: &Request) -> Result<Reply<u64>> {
+        let mut line = serde_json::to_vec(request).chain_err(|| "unable to serialize request")?;
+        line.push(b'\n');
+        self.writer.write_all(&line).chain_err(|| "unable to send request")?;
+        self.read_line()
+    }
+
+    /// Wait for the next line sent by the window manager, without parsing it
+    ///
+    /// # Return value
+    /// The line or `None` if the window manager closed the connection
+    pub fn read_raw_line(&mut self) -> Result<Option<String>> {
+        let mut line = String::new();
+        let read = self.reader.read_line(&mut line).chain_err(|| "unable to receive")?;
+        Ok(if read == 0 { None } else { Some(line) })
+    }
+
+    fn read_line(&mut self) -> Result<Reply<u64>> {
+        match self.read_raw_line()? {
+            Some(line) => serde_json::from_str(&line).chain_err(|| "invalid reply"),
+            None => bail!("connection closed by the window manager"),
+        }
+    }
+}
@@ -0,0 +1,506 @@
+//! Compatibility with the IPC protocol of the [i3] window manager,
+//! so tools written for i3, e.g. polybar's `internal/i3` module,
+//! i3ipc scripts or autotiling, work with sabiwm as well.
+//!
+//! The listener is enabled with `i3-ipc = true` in the configuration.
+//! Like i3, the socket is announced through the `I3SOCK` environment
+//! variable of spawned programs and the `I3_SOCKET_PATH` property
+//! of the root window.
+//!
+//! Every message starts with the magic string `i3-ipc`, followed by
+//! the length of the JSON payload and the message type as 32 bit
+//! integers in native byte order. sabiwm's model is mapped onto i3's:
+//!
+//! * every screen is an output called `screen-<n>`
+//! * workspaces are named after their tag, hidden workspaces
+//!   belong to the first output
+//! * windows are containers whose id is the window id, tiled windows
+//!   are placed side by side, floating windows are wrapped in
+//!   floating containers
+//! * `RUN_COMMAND` understands the common i3 commands, e.g.
+//!   `workspace number 2`, `focus left`, `move container to
+//!   workspace 3`, `kill` or `exec`, as well as every [`Command`].
+//!   Like in i3, `;` and `,` inside quotes don't separate commands,
+//!   and `exec` runs the rest of the payload
+//!
+//! Marks, modes, bar configurations and binding events don't exist
+//! in sabiwm and are answered with empty replies.
+//!
+//! [i3]: https://i3wm.org/docs/ipc.html
+//! [`Command`]: ../../command/enum.Command.html
+
+mod server;
+
+pub(crate) use ipc::i3::server::I3Server;
+
+use command::Command;
+use core::Rectangle;
+use errors::*;
+use ipc::{self, Notification, WindowInfo, WorkspaceInfo};
+use layout::LayoutMessage;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+const RUN_COMMAND: u32 = 0;
+const GET_WORKSPACES: u32 = 1;
+const SUBSCRIBE: u32 = 2;
+const GET_OUTPUTS: u32 = 3;
+const GET_TREE: u32 = 4;
+const GET_MARKS: u32 = 5;
+const GET_BAR_CONFIG: u32 = 6;
+const GET_VERSION: u32 = 7;
+const GET_BINDING_MODES: u32 = 8;
+const GET_CONFIG: u32 = 9;
+const SEND_TICK: u32 = 10;
+const SYNC: u32 = 11;
+const GET_BINDING_STATE: u32 = 12;
+
+const EVENT_WORKSPACE: u32 = 0x8000_0000;
+const EVENT_OUTPUT: u32 = 0x8000_0001;
+const EVENT_WINDOW: u32 = 0x8000_0003;
+const EVENT_SHUTDOWN: u32 = 0x8000_0006;
+const EVENT_TICK: u32 = 0x8000_0007;
+
+/// Node ids of the containers that are not windows, above
+/// the 32 bit range of window ids
+const ROOT_ID: u64 = 1 << 32;
+const OUTPUT_IDS: u64 = 2 << 32;
+const CONTENT_IDS: u64 = 3 << 32;
+const WORKSPACE_IDS: u64 = 4 << 32;
+const FLOATING_IDS: u64 = 5 << 32;
+
+/// Returns the path of the i3 compatible socket for the current display
+pub(crate) fn socket_path() -> Result<PathBuf> {
+    let path = ipc::socket_path()?;
+    Ok(path.with_extension("i3.sock"))
+}
+
+/// The name of the subscribable event with the given type
+fn event_name(event: u32) -> &'static str {
+    match event {
+        EVENT_WORKSPACE => "workspace",
+        EVENT_OUTPUT => "output",
+        EVENT_WINDOW => "window",
+        EVENT_SHUTDOWN => "shutdown",
+        EVENT_TICK => "tick",
+        _ => "unknown",
+    }
+}
+
+/// A message only the window manager can answer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Message {
+    /// Run the given i3 commands
+    RunCommand(String),
+    /// List all workspaces
+    GetWorkspaces,
+    /// List all outputs
+    GetOutputs,
+    /// Describe all outputs, workspaces and windows as a tree
+    GetTree,
+}
+
+impl Message {
+    /// The message type of the reply
+    fn reply_type(&self) -> u32 {
+        match *self {
+            Message::RunCommand(_) => RUN_COMMAND,
+            Message::GetWorkspaces => GET_WORKSPACES,
+            Message::GetOutputs => GET_OUTPUTS,
+            Message::GetTree => GET_TREE,
+        }
+    }
+}
+
+/// Everything the replies are built from
+pub(crate) struct State<Window> {
+    /// All workspaces, ordered by id
+    pub(crate) workspaces: Vec<WorkspaceInfo<Window>>,
+    /// All managed windows with their geometry
+    pub(crate) windows: Vec<(WindowInfo<Window>, Rectangle)>,
+    /// The geometry of all screens
+    pub(crate) screens: Vec<Rectangle>,
+    /// The focused window, if any
+    pub(crate) focus: Option<Window>,
+}
+
+/// The number i3 derives from a workspace name, `-1` if it
+/// does not start with one
+fn number(name: &str) -> i64 {
+    let digits: String = name.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().unwrap_or(-1)
+}
+
+fn output_name(screen: u32) -> String {
+    format!("screen-{}", screen)
+}
+
+fn rect(rectangle: Rectangle) -> Value {
+    json!({
+        "x": rectangle.x(),
+        "y": rectangle.y(),
+        "width": rectangle.width(),
+        "height": rectangle.height(),
+    })
+}
+
+/// The screen a workspace belongs to, the first one if it is hidden
+fn screen_of<Window>(workspace: &WorkspaceInfo<Window>) -> u32 {
+    workspace.screen.unwrap_or(0)
+}
+
+fn screen_rect(screens: &[Rectangle], screen: u32) -> Rectangle {
+    screens.get(screen as usize).cloned().unwrap_or_else(|| Rectangle::new(0, 0, 0, 0))
+}
+
+/// A container with all the fields i3 clients rely on
+fn node(id: Value,
+        kind: &str,
+        name: Option<&str>,
+        rectangle: Rectangle,
+        nodes: Vec<Value>)
+        -> Value {
+    json!({
+        "id": id,
+        "type": kind,
+        "name": name,
+        "rect": rect(rectangle),
+        "window_rect": rect(rectangle),
+        "focused": false,
+        "urgent": false,
+        "layout": "splith",
+        "orientation": "horizontal",
+        "border": "none",
+        "fullscreen_mode": 0,
+        "window": null,
+        "marks": [],
+        "focus": [],
+        "nodes": nodes,
+        "floating_nodes": [],
+    })
+}
+
+fn window_node<Window: Serialize>(window: &WindowInfo<Window>,
+                                  rectangle: Rectangle,
+                                  focused: bool)
+                                  -> Value {
+    let mut node = node(json!(window.id), "con", window.name.as_deref(), rectangle, Vec::new());
+    node["window"] = json!(window.id);
+    node["focused"] = json!(focused);
+    node["window_properties"] = json!({
+        "class": window.class,
+        "title": window.name,
+    });
+    node
+}
+
+fn workspace_node<Window>(workspace: &WorkspaceInfo<Window>,
+                          screens: &[Rectangle],
+                          nodes: Vec<Value>,
+                          floating_nodes: Vec<Value>)
+                          -> Value {
+    let rectangle = screen_rect(screens, screen_of(workspace));
+    let mut node = node(json!(WORKSPACE_IDS + workspace.id as u64),
+                        "workspace",
+                        Some(&workspace.tag),
+                        rectangle,
+                        nodes);
+    node["num"] = json!(number(&workspace.tag));
+    node["output"] = json!(output_name(screen_of(workspace)));
+    node["floating_nodes"] = json!(floating_nodes);
+    node
+}
+
+/// The reply to `GET_WORKSPACES`
+pub(crate) fn workspaces<Window>(state: &State<Window>) -> Value {
+    let workspaces: Vec<Value> = state.workspaces
+        .iter()
+        .map(|workspace| {
+            json!({
+                "id": WORKSPACE_IDS + workspace.id as u64,
+                "num": number(&workspace.tag),
+                "name": workspace.tag,
+                "visible": workspace.screen.is_some(),
+                "focused": workspace.focused,
+                "urgent": false,
+                "rect": rect(screen_rect(&state.screens, screen_of(workspace))),
+                "output": output_name(screen_of(workspace)),
+            })
+        })
+        .collect();
+    json!(workspaces)
+}
+
+/// The reply to `GET_OUTPUTS`
+pub(crate) fn outputs<Window>(state: &State<Window>) -> Value {
+    let outputs: Vec<Value> = state.screens
+        .iter()
+        .enumerate()
+        .map(|(screen, &rectangle)| {
+            let current = state.workspaces
+                .iter()
+                .find(|w| w.screen == Some(screen as u32))
+                .map(|w| w.tag.clone());
+            json!({
+                "name": output_name(screen as u32),
+                "active": true,
+                "primary": screen == 0,
+                "current_workspace": current,
+                "rect": rect(rectangle),
+            })
+        })
+        .collect();
+    json!(outputs)
+}
+
+/// The reply to `GET_TREE`: the root contains one output per
+/// screen, each containing a content container with the workspaces
+pub(crate) fn tree<Window>(state: &State<Window>) -> Value
+    where Window: Copy + PartialEq + Serialize
+{
+    let mut floating_id = FLOATING_IDS;
+    let mut workspaces: Vec<(u32, Value)> = Vec::new();
+    for workspace in &state.workspaces {
+        let mut tiled = Vec::new();
+        let mut floating = Vec::new();
+        for id in &workspace.windows {
+            let (window, rectangle) = match state.windows.iter().find(|(w, _)| w.id == *id) {
+                Some((window, rectangle)) => (window, *rectangle),
+                None => continue,
+            };
+            let node = window_node(window, rectangle, state.focus == Some(*id));
+            if window.floating {
+                floating.push(self::node(json!(floating_id),
+                                         "floating_con",
+                                         None,
+                                         rectangle,
+                                         vec![node]));
+                floating_id += 1;
+            } else {
+                tiled.push(node);
+            }
+        }
+        workspaces.push((screen_of(workspace),
+                         workspace_node(workspace, &state.screens, tiled, floating)));
+    }
+
+    let outputs: Vec<Value> = state.screens
+        .iter()
+        .enumerate()
+        .map(|(screen, &rectangle)| {
+            let screen = screen as u32;
+            let workspaces = workspaces.iter()
+                .filter(|&&(s, _)| s == screen)
+                .map(|(_, node)| node.clone())
+                .collect();
+            let content = node(json!(CONTENT_IDS + screen as u64),
+                               "con",
+                               Some("content"),
+                               rectangle,
+                               workspaces);
+            node(json!(OUTPUT_IDS + screen as u64),
+                 "output",
+                 Some(&output_name(screen)),
+                 rectangle,
+                 vec![content])
+        })
+        .collect();
+
+    let bounds = state.screens
+        .iter()
+        .fold(None, |b: Option<Rectangle>, s| Some(b.map_or(*s, |b| b.union(s))))
+        .unwrap_or_else(|| Rectangle::new(0, 0, 0, 0));
+    node(json!(ROOT_ID), "root", Some("root"), bounds, outputs)
+}
+
+/// The reply to `GET_VERSION`
+fn version() -> Value {
+    json!({
+        "major": 4,
+        "minor": 0,
+        "patch": 0,
+        "human_readable": format!("sabiwm {} (i3 compatible)", env!("CARGO_PKG_VERSION")),
+        "loaded_config_file_name": ::config::path().ok(),
+    })
+}
+
+/// Find the workspace an i3 command refers to, either by its
+/// name or, after `number`, by the number its name starts with
+fn find_workspace<Window>(words: &[&str], workspaces: &[WorkspaceInfo<Window>]) -> Result<u32> {
+    let found = match words {
+        ["number", name @ ..] if !name.is_empty() => {
+            let wanted = number(&name.join(" "));
+            workspaces.iter().find(|w| wanted >= 0 && number(&w.tag) == wanted)
+        }
+        _ => {
+            let name = words.join(" ");
+            workspaces.iter().find(|w| w.tag == name)
+        }
+    };
+    match found {
+        Some(workspace) => Ok(workspace.id),
+        None => bail!("no workspace \"{}\"", words.join(" ")),
+    }
+}
+
+/// Translate a single i3 command into a [`Command`]
+///
+/// # Return value
+/// The [`Command`], `None` for commands that don't do anything,
+/// or an error if there is no equivalent
+///
+/// [`Command`]: ../../command/enum.Command.html
+fn parse_command<Window>(command: &str,
+                         workspaces: &[WorkspaceInfo<Window>])
+                         -> Result<Option<Command>> {
+    let words = words(command);
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let command = match words.as_slice() {
+        [] | ["nop", ..] => return Ok(None),
+        [first, ..] if first.starts_with('[') => bail!("criteria are not supported"),
+        ["exec", ..] => {
+            let program = command.trim_start()["exec".len()..].trim_start();
+            let program = program.trim_start_matches("--no-startup-id").trim();
+            if program.is_empty() {
+                bail!("nothing to execute");
+            }
+            Command::Spawn(String::from(program))
+        }
+        ["workspace", name @ ..] => Command::View(find_workspace(name, workspaces)?),
+        ["move", "container", "to", "workspace", name @ ..] |
+        ["move", "window", "to", "workspace", name @ ..] |
+        ["move", "to", "workspace", name @ ..] => {
+            Command::Shift(find_workspace(name, workspaces)?)
+        }
+        ["focus", "left"] | ["focus", "up"] => Command::FocusUp,
+        ["focus", "right"] | ["focus", "down"] => Command::FocusDown,
+        ["move", "left"] | ["move", "up"] => Command::SwapUp,
+        ["move", "right"] | ["move", "down"] => Command::SwapDown,
+        ["floating", "disable"] => Command::Sink,
+        ["layout", "toggle", ..] => Command::SendLayoutMessage(LayoutMessage::NextLayout),
+        ["kill"] => Command::Kill,
+        ["reload"] => Command::Reload,
+        ["restart"] => Command::Restart,
+        ["exit"] => Command::Quit,
+        _ => {
+            command.trim()
+                .parse()
+                .map_err(|_| format!("unsupported command \"{}\"", command.trim()))?
+        }
+    };
+    Ok(Some(command))
+}
+
+/// Split an i3 command into words. Like in i3, words can be
+/// quoted with `"`, and `\"` stands for a quote inside them.
+fn words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quoted = false;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                word.get_or_insert_with(String::new);
+            }
+            '\\' if quoted => word.get_or_insert_with(String::new).extend(chars.next()),
+            c if c.is_whitespace() && !quoted => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+/// Split the payload of `RUN_COMMAND` into single commands, which
+/// are separated by `;` or `,` outside of quotes. `exec` takes the
+/// rest of the payload as the program, which may contain both.
+fn split_commands(payload: &str) -> Vec<&str> {
+    let is_exec = |command: &str| command.split_whitespace().next() == Some("exec");
+    let mut commands = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (index, c) in payload.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' | ',' if !quoted && !is_exec(&payload[start..index]) => {
+                commands.push(&payload[start..index]);
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+    commands.push(&payload[start..]);
+    commands
+}
+
+/// Translate the payload of `RUN_COMMAND`, i.e. a list of
+/// i3 commands separated by `;` or `,`
+pub(crate) fn parse_commands<Window>(payload: &str,
+                                     workspaces: &[WorkspaceInfo<Window>])
+                                     -> Vec<Result<Option<Command>>> {
+    split_commands(payload)
+        .into_iter()
+        .filter(|c| !c.trim().is_empty())
+        .map(|c| parse_command(c, workspaces))
+        .collect()
+}
+
+/// The reply to `RUN_COMMAND`, one result per command
+pub(crate) fn command_results(commands: &[Result<Option<Command>>]) -> Value {
+    let results: Vec<Value> = commands.iter()
+        .map(|command| match *command {
+            Ok(_) => json!({ "success": true }),
+            Err(ref err) => json!({ "success": false, "error": err.to_string() }),
+        })
+        .collect();
+    json!(results)
+}
+
+/// Translate a [`Notification`] into the corresponding i3 event
+///
+/// # Return value
+/// The event type and payload, `None` if i3 has no such event
+///
+/// [`Notification`]: ../enum.Notification.html
+fn event<Window>(notification: &Notification<Window>, screens: &[Rectangle]) -> Option<(u32, Value)>
+    where Window: Serialize
+{
+    let unknown = Rectangle::new(0, 0, 0, 0);
+    let window = |change: &str, node: Value| json!({ "change": change, "container": node });
+    let bare = |id: &Window, name: Option<&str>| {
+        let mut node = node(json!(id), "con", name, unknown, Vec::new());
+        node["window"] = json!(id);
+        node
+    };
+
+    match *notification {
+        Notification::WorkspaceChanged { ref workspace } => {
+            let current = workspace_node(workspace, screens, Vec::new(), Vec::new());
+            Some((EVENT_WORKSPACE, json!({ "change": "focus", "current": current, "old": null })))
+        }
+        Notification::FocusChanged { window: Some(ref info) } => {
+            Some((EVENT_WINDOW, window("focus", window_node(info, unknown, true))))
+        }
+        Notification::WindowManaged { window: ref info } => {
+            Some((EVENT_WINDOW, window("new", window_node(info, unknown, false))))
+        }
+        Notification::WindowUnmanaged { window: ref id } => {
+            Some((EVENT_WINDOW, window("close", bare(id, None))))
+        }
+        Notification::TitleChanged { window: ref id, ref name } => {
+            Some((EVENT_WINDOW, window("title", bare(id, Some(name)))))
+        }
+        Notification::ScreensChanged { .. } => {
+            Some((EVENT_OUTPUT, json!({ "change": "unspecified" })))
+        }
+        Notification::FocusChanged { window: None } |
+        Notification::LayoutChanged { .. } |
+        Notification::ConfigReloaded => None,
+    }
+}
@@ -0,0 +1,308 @@
+//! The listening side of the i3 compatible socket. Everything that
+//! does not depend on the window manager's state is answered here.
+
+use super::*;
+use errors::*;
+use ipc::server::{MAX_PAYLOAD, accept, bind, flush, poll_fd, receive, write};
+use nix::poll::{PollFd, PollFlags};
+use serde_json;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::Read;
+use std::os::fd::AsFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+
+/// The start of every message
+const MAGIC: &[u8] = b"i3-ipc";
+/// The magic string, the payload length and the message type
+const HEADER_LEN: usize = 14;
+
+/// A connected i3 IPC client
+struct Client {
+    id: usize,
+    stream: UnixStream,
+    buffer: Vec<u8>,
+    pending: Vec<u8>,
+    subscriptions: HashSet<String>,
+    /// The client closed its side of the connection and
+    /// is dropped once all replies have been sent
+    hung_up: bool,
+}
+
+/// The i3 compatible socket together with all connected clients.
+/// The socket is removed when the server is dropped.
+pub(crate) struct I3Server {
+    path: PathBuf,
+    listener: UnixListener,
+    clients: Vec<Client>,
+    next_id: usize,
+}
+
+/// Encode a message with the given type and payload
+fn frame(kind: u32, payload: &Value) -> Vec<u8> {
+    let payload = payload.to_string().into_bytes();
+    let mut message = Vec::with_capacity(HEADER_LEN + payload.len());
+    message.extend_from_slice(MAGIC);
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&kind.to_ne_bytes());
+    message.extend(payload);
+    message
+}
+
+/// Take the next complete message out of the buffer
+///
+/// # Return value
+/// The message type and payload, `None` if the message is not
+/// complete yet or an error if the buffer does not hold a message
+fn next_message(buffer: &mut Vec<u8>) -> Result<Option<(u32, Vec<u8>)>> {
+    let magic = MAGIC.len().min(buffer.len());
+    if buffer[..magic] != MAGIC[..magic] {
+        bail!("invalid magic string");
+    }
+    if buffer.len() < HEADER_LEN {
+        return Ok(None);
+    }
+
+    let integer = |at: usize| {
+        u32::from_ne_bytes([buffer[at], buffer[at + 1], buffer[at + 2], buffer[at + 3]])
+    };
+    let length = integer(MAGIC.len()) as usize;
+    let kind = integer(MAGIC.len() + 4);
+    if length > MAX_PAYLOAD {
+        bail!("message of {} bytes is too large", length);
+    }
+    if buffer.len() < HEADER_LEN + length {
+        return Ok(None);
+    }
+
+    let payload = buffer.drain(..HEADER_LEN + length).skip(HEADER_LEN).collect();
+    Ok(Some((kind, payload)))
+}
+
+/// The content of the configuration file, if there is one
+fn config_content() -> String {
+    let mut content = String::new();
+    if let Ok(path) = ::config::path() {
+        let _ = File::open(path).and_then(|mut f| f.read_to_string(&mut content));
+    }
+    content
+}
+
+impl I3Server {
+    /// Create the socket at the given path. The socket and its
+    /// directory are only accessible by the current user.
+    pub(crate) fn bind(path: &Path) -> Result<I3Server> {
+        let listener = bind(path)?;
+        info!("listening for i3 IPC requests on {}", path.display());
+        Ok(I3Server {
+            path: path.to_path_buf(),
+            listener,
+            clients: Vec::new(),
+            next_id: 0,
+        })
+    }
+
+    /// The path of the socket
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The file descriptors of the socket and all clients to poll,
+    /// waiting for clients to accept queued messages as well
+    pub(crate) fn poll_fds(&self) -> Vec<PollFd<'_>> {
+        let clients = self.clients.iter().map(|c| poll_fd(&c.stream, &c.pending, c.hung_up));
+        Some(PollFd::new(self.listener.as_fd(), PollFlags::POLLIN))
+            .into_iter()
+            .chain(clients)
+            .collect()
+    }
+
+    /// Accept new clients, send queued messages, read all complete
+    /// messages and answer those that don't need the window manager's state
+    ///
+    /// # Return value
+    /// The id of the sending client together with each
+    /// [`Message`] the window manager has to answer
+    ///
+    /// [`Message`]: enum.Message.html
+    pub(crate) fn messages(&mut self) -> Vec<(usize, Message)> {
+        for stream in accept(&self.listener) {
+            debug!("accepted i3 IPC client {}", self.next_id);
+            self.clients.push(Client {
+                id: self.next_id,
+                stream,
+                buffer: Vec::new(),
+                pending: Vec::new(),
+                subscriptions: HashSet::new(),
+                hung_up: false,
+            });
+            self.next_id += 1;
+        }
+
+        let mut received = Vec::new();
+        let mut closed = Vec::new();
+        for client in &mut self.clients {
+            if let Err(err) = flush(&mut client.stream, &mut client.pending) {
+                debug!("dropping i3 IPC client {}: {}", client.id, err);
+                closed.push(client.id);
+                continue;
+            }
+            if client.hung_up {
+                if client.pending.is_empty() {
+                    debug!("i3 IPC client {} disconnected", client.id);
+                    closed.push(client.id);
+                }
+                continue;
+            }
+            // Messages sent right before hanging up are still answered
+            if !receive(&mut client.stream, &mut client.buffer, HEADER_LEN + MAX_PAYLOAD) {
+                trace!("i3 IPC client {} hung up", client.id);
+                client.hung_up = true;
+            }
+
+            loop {
+                match next_message(&mut client.buffer) {
+                    Ok(Some((kind, payload))) => received.push((client.id, kind, payload)),
+                    Ok(None) => break,
+                    Err(err) => {
+                        debug!("dropping i3 IPC client {}: {}", client.id, err);
+                        closed.push(client.id);
+                        break;
+                    }
+                }
+            }
+        }
+        self.clients.retain(|c| !closed.contains(&c.id));
+
+        received.into_iter()
+            .filter_map(|(id, kind, payload)| {
+                let payload = String::from_utf8_lossy(&payload).into_owned();
+                trace!("i3 IPC message {} from client {}: {}", kind, id, payload);
+                self.handle(id, kind, payload).map(|message| (id, message))
+            })
+            .collect()
+    }
+
+    /// Send the window manager's answer to a [`Message`]
+    ///
+    /// [`Message`]: enum.Message.html
+    pub(crate) fn reply(&mut self, id: usize, message: &Message, payload: &Value) {
+        self.send(id, &frame(message.reply_type(), payload));
+    }
+
+    /// Send the i3 event corresponding to the [`Notification`]
+    /// to all clients subscribed to it
+    ///
+    /// [`Notification`]: ../enum.Notification.html
+    pub(crate) fn notify<Window>(&mut self,
+                                 notification: &Notification<Window>,
+                                 screens: &[Rectangle])
+        where Window: Serialize
+    {
+        if let Some((kind, payload)) = event(notification, screens) {
+            self.broadcast(kind, &payload);
+        }
+    }
+
+    /// Tell subscribed clients that the window manager
+    /// is about to `"exit"` or `"restart"`
+    pub(crate) fn shutdown(&mut self, change: &str) {
+        self.broadcast(EVENT_SHUTDOWN, &json!({ "change": change }));
+    }
+
+    /// Answer a message if possible
+    ///
+    /// # Return value
+    /// The [`Message`] if the window manager has to answer it
+    ///
+    /// [`Message`]: enum.Message.html
+    fn handle(&mut self, id: usize, kind: u32, payload: String) -> Option<Message> {
+        let reply = match kind {
+            RUN_COMMAND => return Some(Message::RunCommand(payload)),
+            GET_WORKSPACES => return Some(Message::GetWorkspaces),
+            GET_OUTPUTS => return Some(Message::GetOutputs),
+            GET_TREE => return Some(Message::GetTree),
+            SUBSCRIBE => {
+                self.subscribe(id, &payload);
+                return None;
+            }
+            GET_MARKS => json!([]),
+            GET_BAR_CONFIG if payload.is_empty() => json!([]),
+            GET_BAR_CONFIG => json!({ "id": payload }),
+            GET_VERSION => version(),
+            GET_BINDING_MODES => json!(["default"]),
+            GET_CONFIG => json!({ "config": config_content() }),
+            SEND_TICK => {
+                self.broadcast(EVENT_TICK, &json!({ "first": false, "payload": payload }));
+                json!({ "success": true })
+            }
+            SYNC => json!({ "success": true }),
+            GET_BINDING_STATE => json!({ "name": "default" }),
+            _ => {
+                debug!("unknown i3 IPC message type {} from client {}", kind, id);
+                json!({ "success": false, "error": "unknown message type" })
+            }
+        };
+        self.send(id, &frame(kind, &reply));
+        None
+    }
+
+    /// Subscribe the client to the events in the JSON array and reply
+    fn subscribe(&mut self, id: usize, payload: &str) {
+        let events: Vec<String> = match serde_json::from_str(payload) {
+            Ok(events) => events,
+            Err(err) => {
+                let reply = json!({ "success": false, "error": err.to_string() });
+                return self.send(id, &frame(SUBSCRIBE, &reply));
+            }
+        };
+
+        debug!("i3 IPC client {} subscribed to {:?}", id, events);
+        let tick = events.iter().any(|e| e == "tick");
+        if let Some(client) = self.clients.iter_mut().find(|c| c.id == id) {
+            client.subscriptions.extend(events);
+        }
+        self.send(id, &frame(SUBSCRIBE, &json!({ "success": true })));
+
+        // Like i3, greet new tick subscribers with a first tick
+        if tick {
+            self.send(id, &frame(EVENT_TICK, &json!({ "first": true, "payload": "" })));
+        }
+    }
+
+    fn broadcast(&mut self, kind: u32, payload: &Value) {
+        let name = event_name(kind);
+        let message = frame(kind, payload);
+        let subscribers: Vec<usize> = self.clients
+            .iter()
+            .filter(|c| c.subscriptions.contains(name))
+            .map(|c| c.id)
+            .collect();
+        for id in subscribers {
+            self.send(id, &message);
+        }
+    }
+
+    /// Send an encoded message to the given client. Clients
+    /// that can't be written to or fall too far behind are dropped.
+    fn send(&mut self, id: usize, message: &[u8]) {
+        let index = match self.clients.iter().position(|c| c.id == id) {
+            Some(index) => index,
+            None => return,
+        };
+
+        let client = &mut self.clients[index];
+        if let Err(err) = write(&mut client.stream, &mut client.pending, message) {
+            debug!("dropping i3 IPC client {}: {}", id, err);
+            self.clients.remove(index);
+        }
+    }
+}
+
+impl Drop for I3Server {
+    fn drop(&mut self) {
+        debug!("removing socket {}", self.path.display());
+        let _ = fs::remove_file(&self.path);
+    }
+}
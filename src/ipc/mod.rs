@@ -0,0 +1,289 @@
+//! The IPC module allows other processes, e.g. scripts and status
+//! bars, to control the window manager over a Unix socket at
+//! `$XDG_RUNTIME_DIR/sabiwm/<display>.sock`.
+//!
+//! The protocol is line based: every [`Request`] is a single line
+//! of JSON and is answered by a single line of JSON, the [`Reply`].
+//!
+//! ```text
+//! {"type":"command","command":"view 2"}
+//! {"type":"success"}
+//! {"type":"get_focused"}
+//! {"type":"focused","window":{"id":4194307,"name":"vim","class":"XTerm","workspace":2,"floating":false}}
+//! ```
+//!
+//! After a [`Request::Subscribe`], the client additionally receives a
+//! [`Notification`] line for every event of the subscribed kinds.
+//!
+//! ```text
+//! {"type":"subscribe","events":["workspace","focus"]}
+//! {"type":"success"}
+//! {"event":"workspace_changed","workspace":{"id":1,"tag":"2",...}}
+//! ```
+//!
+//! With `i3-ipc = true` in the configuration, a second socket next to
+//! it speaks the binary IPC protocol of i3, so tools written for i3
+//! work as well.
+//!
+//! [`Notification`]: enum.Notification.html
+//! [`Reply`]: enum.Reply.html
+//! [`Request`]: enum.Request.html
+//! [`Request::Subscribe`]: enum.Request.html#variant.Subscribe
+
+mod client;
+pub(crate) mod i3;
+mod server;
+
+pub use ipc::client::Client;
+pub(crate) use ipc::server::IpcServer;
+
+use command::Command;
+use core::Rectangle;
+use errors::*;
+use std::env;
+use std::path::PathBuf;
+use xdg::BaseDirectories;
+
+/// Returns the path of the IPC socket for the current display.
+/// It can be overridden with the `SABIWM_SOCKET` environment variable.
+pub fn socket_path() -> Result<PathBuf> {
+    if let Some(path) = env::var_os("SABIWM_SOCKET") {
+        return Ok(PathBuf::from(path));
+    }
+
+    let xdg = BaseDirectories::with_prefix("sabiwm")
+        .chain_err(|| "unable to get xdg base directory")?;
+    let runtime = xdg.get_runtime_directory().chain_err(|| "XDG_RUNTIME_DIR is not set")?;
+    Ok(runtime.join("sabiwm").join(format!("{}.sock", display_name())))
+}
+
+/// The name of the current display, e.g. `0` for `DISPLAY=:0.0`
+fn display_name() -> String {
+    let display = env::var("DISPLAY").unwrap_or_default();
+    let display = display.rsplit(':').next().unwrap_or("");
+    match display.split('.').next() {
+        Some(number) if !number.is_empty() => String::from(number),
+        _ => String::from("default"),
+    }
+}
+
+/// A request sent to the window manager
+///
+/// # Examples
+///
+/// ```
+/// # extern crate serde_json;
+/// # extern crate sabiwm;
+/// # use sabiwm::command::Command;
+/// # use sabiwm::ipc::{EventKind, Request};
+/// # fn main() {
+/// let request: Request = serde_json::from_str(r#"{"type":"command","command":"view 2"}"#)
+///     .unwrap();
+/// assert_eq!(Request::Command { command: Command::View(2) }, request);
+///
+/// let request: Request = serde_json::from_str(r#"{"type":"get_workspaces"}"#).unwrap();
+/// assert_eq!(Request::GetWorkspaces, request);
+///
+/// let request: Request = serde_json::from_str(r#"{"type":"subscribe","events":["focus"]}"#)
+///     .unwrap();
+/// assert_eq!(Request::Subscribe { events: vec![EventKind::Focus] }, request);
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Execute the given [`Command`]
+    ///
+    /// [`Command`]: ../command/enum.Command.html
+    Command {
+        /// The command to execute
+        command: Command,
+    },
+    /// Get all workspaces
+    GetWorkspaces,
+    /// Get the focused window
+    GetFocused,
+    /// Receive a [`Notification`] for each event of the given kinds
+    ///
+    /// [`Notification`]: enum.Notification.html
+    Subscribe {
+        /// The kinds of events to subscribe to
+        events: Vec<EventKind>,
+    },
+}
+
+/// Information about a workspace
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceInfo<Window> {
+    /// The id of the workspace
+    pub id: u32,
+    /// The name of the workspace
+    pub tag: String,
+    /// The name of the active layout
+    pub layout: String,
+    /// The screen the workspace is visible on, if any
+    pub screen: Option<u32>,
+    /// Whether this is the focused workspace
+    pub focused: bool,
+    /// All windows on the workspace
+    pub windows: Vec<Window>,
+}
+
+/// Information about a window
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowInfo<Window> {
+    /// The id of the window
+    pub id: Window,
+    /// The name/title of the window
+    pub name: Option<String>,
+    /// The class of the window
+    pub class: Option<String>,
+    /// The id of the workspace the window is on
+    pub workspace: u32,
+    /// Whether the window is floating
+    pub floating: bool,
+}
+
+/// The window manager's answer to a [`Request`]
+///
+/// # Examples
+///
+/// ```
+/// # extern crate serde_json;
+/// # extern crate sabiwm;
+/// # use sabiwm::ipc::Reply;
+/// # fn main() {
+/// let reply : Reply<u32> = Reply::Error { message: String::from("unknown command") };
+/// assert_eq!(r#"{"type":"error","message":"unknown command"}"#,
+///            serde_json::to_string(&reply).unwrap());
+/// # }
+/// ```
+///
+/// [`Request`]: enum.Request.html
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Reply<Window> {
+    /// The request was handled successfully
+    Success,
+    /// All workspaces, ordered by id
+    Workspaces {
+        /// The workspaces
+        workspaces: Vec<WorkspaceInfo<Window>>,
+    },
+    /// The focused window
+    Focused {
+        /// The focused window, if there is one
+        window: Option<WindowInfo<Window>>,
+    },
+    /// The request failed
+    Error {
+        /// What went wrong
+        message: String,
+    },
+}
+
+/// The kinds of events a client can subscribe to
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// The focused workspace changed
+    Workspace,
+    /// The focused window changed
+    Focus,
+    /// A window got managed or unmanaged
+    Window,
+    /// The title of a window changed
+    Title,
+    /// The layout of a workspace changed
+    Layout,
+    /// The screen layout changed
+    Screens,
+    /// The configuration got reloaded
+    Config,
+}
+
+/// An event sent to all clients subscribed to its [`EventKind`]
+///
+/// # Examples
+///
+/// ```
+/// # extern crate serde_json;
+/// # extern crate sabiwm;
+/// # use sabiwm::ipc::{EventKind, Notification};
+/// # fn main() {
+/// let notification : Notification<u32> = Notification::TitleChanged {
+///     window: 3,
+///     name: String::from("vim"),
+/// };
+/// assert_eq!(EventKind::Title, notification.kind());
+/// assert_eq!(r#"{"event":"title_changed","window":3,"name":"vim"}"#,
+///            serde_json::to_string(&notification).unwrap());
+/// # }
+/// ```
+///
+/// [`EventKind`]: enum.EventKind.html
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Notification<Window> {
+    /// Another workspace got focused
+    WorkspaceChanged {
+        /// The newly focused workspace
+        workspace: WorkspaceInfo<Window>,
+    },
+    /// Another window got focused
+    FocusChanged {
+        /// The newly focused window, if any
+        window: Option<WindowInfo<Window>>,
+    },
+    /// A new window is managed
+    WindowManaged {
+        /// The new window
+        window: WindowInfo<Window>,
+    },
+    /// A window is no longer managed
+    WindowUnmanaged {
+        /// The id of the window
+        window: Window,
+    },
+    /// The title of a window changed
+    TitleChanged {
+        /// The id of the window
+        window: Window,
+        /// The new title
+        name: String,
+    },
+    /// The layout of a workspace changed
+    LayoutChanged {
+        /// The id of the workspace
+        workspace: u32,
+        /// The name of the new layout
+        layout: String,
+    },
+    /// The screen layout changed
+    ScreensChanged {
+        /// The new screens
+        screens: Vec<Rectangle>,
+    },
+    /// The configuration got reloaded
+    ConfigReloaded,
+}
+
+impl<Window> Notification<Window> {
+    /// Returns the [`EventKind`] clients need to subscribe to
+    /// in order to receive this [`Notification`]
+    ///
+    /// [`EventKind`]: enum.EventKind.html
+    /// [`Notification`]: enum.Notification.html
+    pub fn kind(&self) -> EventKind {
+        match *self {
+            Notification::WorkspaceChanged { .. } => EventKind::Workspace,
+            Notification::FocusChanged { .. } => EventKind::Focus,
+            Notification::WindowManaged { .. } |
+            Notification::WindowUnmanaged { .. } => EventKind::Window,
+            Notification::TitleChanged { .. } => EventKind::Title,
+            Notification::LayoutChanged { .. } => EventKind::Layout,
+            Notification::ScreensChanged { .. } => EventKind::Screens,
+            Notification::ConfigReloaded => EventKind::Config,
+        }
+    }
+}
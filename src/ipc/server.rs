@@ -0,0 +1,326 @@
+//! The listening side of the IPC socket. All sockets are
+//! non-blocking, so they can be polled together with the backend.
+//! Messages a client does not read right away are queued, and
+//! clients that fall too far behind are dropped.
+
+use errors::*;
+use ipc::{EventKind, Notification, Request};
+use nix::poll::{PollFd, PollFlags};
+use nix::sys::socket::{getsockopt, sockopt};
+use nix::unistd::getuid;
+use serde::Serialize;
+use serde_json;
+use std::collections::HashSet;
+use std::fs::{self, DirBuilder};
+use std::io::{self, ErrorKind, Read, Write};
+use std::os::fd::AsFd;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+
+/// Clients sending larger messages are dropped
+pub(super) const MAX_PAYLOAD: usize = 1 << 20;
+/// Clients with more unsent data are dropped
+const MAX_PENDING: usize = 4 << 20;
+
+/// A connected IPC client
+struct Client {
+    id: usize,
+    stream: UnixStream,
+    buffer: Vec<u8>,
+    pending: Vec<u8>,
+    subscriptions: HashSet<EventKind>,
+    /// The client closed its side of the connection and
+    /// is dropped once all replies have been sent
+    hung_up: bool,
+}
+
+/// The IPC socket together with all connected clients.
+/// The socket is removed when the server is dropped.
+pub(crate) struct IpcServer {
+    path: PathBuf,
+    listener: UnixListener,
+    clients: Vec<Client>,
+    next_id: usize,
+}
+
+impl IpcServer {
+    /// Create the socket at the given path. The socket and its
+    /// directory are only accessible by the current user.
+    pub(crate) fn bind(path: &Path) -> Result<IpcServer> {
+        let listener = bind(path)?;
+        info!("listening for IPC requests on {}", path.display());
+        Ok(IpcServer {
+            path: path.to_path_buf(),
+            listener,
+            clients: Vec::new(),
+            next_id: 0,
+        })
+    }
+
+    /// The file descriptors of the socket and all clients to poll,
+    /// waiting for clients to accept queued messages as well
+    pub(crate) fn poll_fds(&self) -> Vec<PollFd<'_>> {
+        let clients = self.clients.iter().map(|c| poll_fd(&c.stream, &c.pending, c.hung_up));
+        Some(PollFd::new(self.listener.as_fd(), PollFlags::POLLIN))
+            .into_iter()
+            .chain(clients)
+            .collect()
+    }
+
+    /// Accept new clients, send queued messages and read all complete requests
+    ///
+    /// # Return value
+    /// The id of the sending client together with the parsed
+    /// request or a description of why it could not be parsed
+    pub(crate) fn requests(&mut self) -> Vec<(usize, ::std::result::Result<Request, String>)> {
+        self.accept();
+
+        let mut requests = Vec::new();
+        let mut closed = Vec::new();
+        for client in &mut self.clients {
+            if let Err(err) = flush(&mut client.stream, &mut client.pending) {
+                debug!("dropping IPC client {}: {}", client.id, err);
+                closed.push(client.id);
+                continue;
+            }
+            if client.hung_up {
+                if client.pending.is_empty() {
+                    debug!("IPC client {} disconnected", client.id);
+                    closed.push(client.id);
+                }
+                continue;
+            }
+            // Requests sent right before hanging up are still answered
+            if !receive(&mut client.stream, &mut client.buffer, MAX_PAYLOAD + 1) {
+                trace!("IPC client {} hung up", client.id);
+                client.hung_up = true;
+            }
+
+            while let Some(end) = client.buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = client.buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                if line.trim().is_empty() {
+                    continue;
+                }
+                trace!("IPC request from client {}: {}", client.id, line.trim());
+                let request = serde_json::from_str(&line).map_err(|err| err.to_string());
+                requests.push((client.id, request));
+            }
+
+            if client.buffer.len() > MAX_PAYLOAD {
+                debug!("dropping IPC client {}: request is too large", client.id);
+                closed.push(client.id);
+            }
+        }
+
+        self.clients.retain(|c| !closed.contains(&c.id));
+        requests
+    }
+
+    /// Send a single line of JSON to the given client. Clients
+    /// that can't be written to or fall too far behind are dropped.
+    pub(crate) fn send<T: Serialize>(&mut self, id: usize, message: &T) {
+        let mut line = match serde_json::to_vec(message) {
+            Ok(line) => line,
+            Err(err) => {
+                error!("unable to serialize IPC message: {}", err);
+                return;
+            }
+        };
+        line.push(b'\n');
+
+        let index = match self.clients.iter().position(|c| c.id == id) {
+            Some(index) => index,
+            None => return,
+        };
+
+        let client = &mut self.clients[index];
+        if let Err(err) = write(&mut client.stream, &mut client.pending, &line) {
+            debug!("dropping IPC client {}: {}", id, err);
+            self.clients.remove(index);
+        }
+    }
+
+    /// Subscribe the given client to the given kinds of events
+    pub(crate) fn subscribe(&mut self, id: usize, events: &[EventKind]) {
+        if let Some(client) = self.clients.iter_mut().find(|c| c.id == id) {
+            debug!("IPC client {} subscribed to {:?}", id, events);
+            client.subscriptions.extend(events);
+        }
+    }
+
+    /// Send the notification to all clients subscribed to its kind
+    pub(crate) fn notify<Window: Serialize>(&mut self, notification: &Notification<Window>) {
+        let kind = notification.kind();
+        let subscribers: Vec<usize> = self.clients
+            .iter()
+            .filter(|c| c.subscriptions.contains(&kind))
+            .map(|c| c.id)
+            .collect();
+        for id in subscribers {
+            self.send(id, notification);
+        }
+    }
+
+    fn accept(&mut self) {
+        for stream in accept(&self.listener) {
+            debug!("accepted IPC client {}", self.next_id);
+            self.clients.push(Client {
+                id: self.next_id,
+                stream,
+                buffer: Vec::new(),
+                pending: Vec::new(),
+                subscriptions: HashSet::new(),
+                hung_up: false,
+            });
+            self.next_id += 1;
+        }
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        debug!("removing socket {}", self.path.display());
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Create a non-blocking socket at the given path, replacing a stale
+/// one. The socket and its directory are only accessible by the
+/// current user.
+pub(super) fn bind(path: &Path) -> Result<UnixListener> {
+    if let Some(directory) = path.parent() {
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(directory)
+            .chain_err(|| format!("unable to create directory {}", directory.display()))?;
+    }
+
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            bail!("another instance is already listening on {}", path.display());
+        }
+        debug!("removing stale socket {}", path.display());
+        fs::remove_file(path)
+            .chain_err(|| format!("unable to remove stale socket {}", path.display()))?;
+    }
+
+    let listener = UnixListener::bind(path)
+        .chain_err(|| format!("unable to bind socket {}", path.display()))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+        .chain_err(|| format!("unable to restrict socket {}", path.display()))?;
+    listener.set_nonblocking(true).chain_err(|| "unable to set up socket")?;
+    Ok(listener)
+}
+
+/// Accept all pending connections of the current user
+///
+/// # Return value
+/// The non-blocking streams of the new clients
+pub(super) fn accept(listener: &UnixListener) -> Vec<UnixStream> {
+    let mut streams = Vec::new();
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if !is_same_user(&stream) {
+                    warn!("rejecting IPC client of another user");
+                    continue;
+                }
+                match stream.set_nonblocking(true) {
+                    Ok(()) => streams.push(stream),
+                    Err(err) => error!("unable to set up IPC client: {}", err),
+                }
+            }
+            Err(ref err) if err.kind() == ErrorKind::WouldBlock => break,
+            Err(err) => {
+                error!("unable to accept IPC client: {}", err);
+                break;
+            }
+        }
+    }
+    streams
+}
+
+/// Append everything that can be read without blocking to the buffer,
+/// until it holds at least `limit` bytes. The rest is read once the
+/// buffer has been emptied.
+///
+/// # Return value
+/// `false` if the client closed the connection or can't be read from
+pub(super) fn receive(stream: &mut UnixStream, buffer: &mut Vec<u8>, limit: usize) -> bool {
+    let mut chunk = [0; 4096];
+    while buffer.len() < limit {
+        match stream.read(&mut chunk) {
+            Ok(0) => return false,
+            Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+            Err(ref err) if err.kind() == ErrorKind::WouldBlock => return true,
+            Err(ref err) if err.kind() == ErrorKind::Interrupted => (),
+            Err(err) => {
+                debug!("unable to read from IPC client: {}", err);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Queue the message behind the client's pending data and
+/// write as much as possible without blocking
+///
+/// # Return value
+/// An error if the client can't be written to or
+/// would have too much unsent data
+pub(super) fn write(stream: &mut UnixStream,
+                    pending: &mut Vec<u8>,
+                    message: &[u8])
+                    -> io::Result<()> {
+    if pending.len() + message.len() > MAX_PENDING {
+        return Err(io::Error::other("client does not read its messages"));
+    }
+    pending.extend_from_slice(message);
+    flush(stream, pending)
+}
+
+/// Write as much of the pending data as possible without blocking
+///
+/// # Return value
+/// An error if the client can't be written to
+pub(super) fn flush(stream: &mut UnixStream, pending: &mut Vec<u8>) -> io::Result<()> {
+    let mut written = 0;
+    while written < pending.len() {
+        match stream.write(&pending[written..]) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(count) => written += count,
+            Err(ref err) if err.kind() == ErrorKind::WouldBlock => break,
+            Err(ref err) if err.kind() == ErrorKind::Interrupted => (),
+            Err(err) => return Err(err),
+        }
+    }
+    pending.drain(..written);
+    Ok(())
+}
+
+/// Poll the client's stream for requests, and for being writable
+/// while it has pending data. Clients that hung up are only waited
+/// for to accept their pending data, as they would always be readable.
+pub(super) fn poll_fd<'a>(stream: &'a UnixStream, pending: &[u8], hung_up: bool) -> PollFd<'a> {
+    let events = match (pending.is_empty(), hung_up) {
+        (true, _) => PollFlags::POLLIN,
+        (false, false) => PollFlags::POLLIN | PollFlags::POLLOUT,
+        (false, true) => PollFlags::POLLOUT,
+    };
+    PollFd::new(stream.as_fd(), events)
+}
+
+/// Checks that the peer of the given stream runs as the current user
+fn is_same_user(stream: &UnixStream) -> bool {
+    match getsockopt(stream, sockopt::PeerCredentials) {
+        Ok(credentials) => credentials.uid() == getuid().as_raw(),
+        Err(err) => {
+            error!("unable to get IPC client credentials: {}", err);
+            false
+        }
+    }
+}
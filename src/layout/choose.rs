@@ -0,0 +1,89 @@
+use core::{Rectangle, Stack};
+use layout::{Layout, LayoutMessage};
+
+/// Combines several [`Layout`]s of which exactly one is active.
+/// [`LayoutMessage::NextLayout`], [`LayoutMessage::PrevLayout`] and
+/// [`LayoutMessage::FirstLayout`] switch between them, all other
+/// messages are passed on to the active [`Layout`].
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::layout::{Choose, Full, Layout, LayoutMessage, Tall};
+/// let layout : Choose<u32> = Choose::new(vec![Box::new(Tall::default()), Box::new(Full)]);
+/// let next = layout.handle_message(LayoutMessage::NextLayout).unwrap();
+/// assert_eq!("Full", next.name());
+/// ```
+///
+/// [`Layout`]: trait.Layout.html
+/// [`LayoutMessage::NextLayout`]: enum.LayoutMessage.html#variant.NextLayout
+/// [`LayoutMessage::PrevLayout`]: enum.LayoutMessage.html#variant.PrevLayout
+/// [`LayoutMessage::FirstLayout`]: enum.LayoutMessage.html#variant.FirstLayout
+pub struct Choose<Window> {
+    layouts: Vec<Box<dyn Layout<Window>>>,
+    current: usize,
+}
+
+impl<Window: 'static> Choose<Window> {
+    /// Create a new [`Choose`] layout starting with the first given [`Layout`]
+    ///
+    /// # Panics
+    /// If no layouts are given
+    ///
+    /// [`Choose`]: struct.Choose.html
+    /// [`Layout`]: trait.Layout.html
+    pub fn new(layouts: Vec<Box<dyn Layout<Window>>>) -> Choose<Window> {
+        assert!(!layouts.is_empty(), "need at least one layout to choose from");
+        Choose {
+            layouts,
+            current: 0,
+        }
+    }
+
+    fn select(&self, current: usize) -> Option<Box<dyn Layout<Window>>> {
+        if current == self.current {
+            return None;
+        }
+
+        Some(Box::new(Choose {
+            layouts: self.layouts.iter().map(|l| l.copy()).collect(),
+            current,
+        }))
+    }
+}
+
+impl<Window: 'static> Layout<Window> for Choose<Window> {
+    fn name(&self) -> String {
+        self.layouts[self.current].name()
+    }
+
+    fn apply(&self, screen: Rectangle, stack: &Stack<Window>) -> Vec<(Window, Rectangle)> {
+        self.layouts[self.current].apply(screen, stack)
+    }
+
+    fn handle_message(&self, message: LayoutMessage) -> Option<Box<dyn Layout<Window>>> {
+        let count = self.layouts.len();
+        match message {
+            LayoutMessage::NextLayout => self.select((self.current + 1) % count),
+            LayoutMessage::PrevLayout => self.select((self.current + count - 1) % count),
+            LayoutMessage::FirstLayout => self.select(0),
+            _ => {
+                self.layouts[self.current].handle_message(message).map(|layout| {
+                    let mut layouts: Vec<_> = self.layouts.iter().map(|l| l.copy()).collect();
+                    layouts[self.current] = layout;
+                    Box::new(Choose {
+                        layouts,
+                        current: self.current,
+                    }) as Box<dyn Layout<Window>>
+                })
+            }
+        }
+    }
+
+    fn copy(&self) -> Box<dyn Layout<Window>> {
+        Box::new(Choose {
+            layouts: self.layouts.iter().map(|l| l.copy()).collect(),
+            current: self.current,
+        })
+    }
+}
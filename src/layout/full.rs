@@ -0,0 +1,43 @@
+use core::{Rectangle, Stack};
+use layout::{Layout, LayoutMessage};
+use std::fmt::Debug;
+
+/// Shows only the focused window, using the whole screen.
+/// All other windows are hidden. As there is nothing to adjust,
+/// [`Full`] ignores all [`LayoutMessage`]s.
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::core::{Rectangle, Stack};
+/// # use sabiwm::layout::{Full, Layout, LayoutMessage};
+/// let screen = Rectangle::new(0, 0, 1280, 800);
+/// let stack = Stack::new(2, vec![1], vec![3]);
+///
+/// assert_eq!(vec![(2, Rectangle::new(0, 0, 1280, 800))],
+///            Full.apply(screen, &stack));
+/// assert!(Layout::<u32>::handle_message(&Full, LayoutMessage::Expand).is_none());
+/// ```
+///
+/// [`Full`]: struct.Full.html
+/// [`LayoutMessage`]: enum.LayoutMessage.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Full;
+
+impl<Window: Copy + Clone + Eq + Debug + 'static> Layout<Window> for Full {
+    fn name(&self) -> String {
+        String::from("Full")
+    }
+
+    fn apply(&self, screen: Rectangle, stack: &Stack<Window>) -> Vec<(Window, Rectangle)> {
+        vec![(stack.focus, screen)]
+    }
+
+    fn handle_message(&self, _: LayoutMessage) -> Option<Box<dyn Layout<Window>>> {
+        None
+    }
+
+    fn copy(&self) -> Box<dyn Layout<Window>> {
+        Box::new(*self)
+    }
+}
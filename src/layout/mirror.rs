@@ -0,0 +1,65 @@
+use core::{Rectangle, Stack};
+use layout::{Layout, LayoutMessage};
+
+/// Rotates another [`Layout`] by 90°. For example, a mirrored
+/// [`Tall`] layout places the master area at the top of the
+/// screen and all other windows below it.
+///
+/// All [`LayoutMessage`]s are passed on to the mirrored [`Layout`].
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::core::{Rectangle, Stack};
+/// # use sabiwm::layout::{Layout, Mirror, Tall};
+/// let mirror = Mirror::new(Box::new(Tall::new(1, 0.5, 0.1)));
+/// let screen = Rectangle::new(0, 0, 1920, 1080);
+/// let stack = Stack::new(1, vec![], vec![2, 3]);
+///
+/// assert_eq!("Mirror Tall", Layout::<u32>::name(&mirror));
+/// assert_eq!(vec![(1, Rectangle::new(0, 0, 1920, 540)),
+///                 (2, Rectangle::new(0, 540, 960, 540)),
+///                 (3, Rectangle::new(960, 540, 960, 540))],
+///            mirror.apply(screen, &stack));
+/// ```
+///
+/// [`Layout`]: trait.Layout.html
+/// [`LayoutMessage`]: enum.LayoutMessage.html
+/// [`Tall`]: struct.Tall.html
+pub struct Mirror<Window> {
+    layout: Box<dyn Layout<Window>>,
+}
+
+impl<Window: 'static> Mirror<Window> {
+    /// Create a new [`Mirror`] of the given [`Layout`]
+    ///
+    /// [`Layout`]: trait.Layout.html
+    /// [`Mirror`]: struct.Mirror.html
+    pub fn new(layout: Box<dyn Layout<Window>>) -> Mirror<Window> {
+        Mirror { layout }
+    }
+}
+
+impl<Window: 'static> Layout<Window> for Mirror<Window> {
+    fn name(&self) -> String {
+        format!("Mirror {}", self.layout.name())
+    }
+
+    fn apply(&self, screen: Rectangle, stack: &Stack<Window>) -> Vec<(Window, Rectangle)> {
+        self.layout
+            .apply(screen.transpose(), stack)
+            .into_iter()
+            .map(|(window, rectangle)| (window, rectangle.transpose()))
+            .collect()
+    }
+
+    fn handle_message(&self, message: LayoutMessage) -> Option<Box<dyn Layout<Window>>> {
+        self.layout
+            .handle_message(message)
+            .map(|layout| Box::new(Mirror::new(layout)) as Box<dyn Layout<Window>>)
+    }
+
+    fn copy(&self) -> Box<dyn Layout<Window>> {
+        Box::new(Mirror::new(self.layout.copy()))
+    }
+}
@@ -0,0 +1,114 @@
+//! The layout module contains everything related to arranging
+//! windows on a screen.
+//!
+//! A [`Layout`] takes the [`Stack`] of a [`Workspace`] and the
+//! [`Rectangle`] of the screen it is shown on and computes the
+//! geometry of each window. Layouts can be adjusted at runtime
+//! by sending them a [`LayoutMessage`].
+//!
+//! [`Layout`]: trait.Layout.html
+//! [`LayoutMessage`]: enum.LayoutMessage.html
+//! [`Rectangle`]: ../core/struct.Rectangle.html
+//! [`Stack`]: ../core/struct.Stack.html
+//! [`Workspace`]: ../core/struct.Workspace.html
+
+mod choose;
+mod full;
+mod mirror;
+mod tall;
+
+pub use layout::choose::Choose;
+pub use layout::full::Full;
+pub use layout::mirror::Mirror;
+pub use layout::tall::Tall;
+
+use core::{Rectangle, Stack};
+use std::fmt::Debug;
+
+/// The [`Layout`]s every [`Workspace`] starts with: [`Tall`],
+/// its [`Mirror`] and [`Full`], switchable via [`LayoutMessage::NextLayout`].
+///
+/// [`Full`]: struct.Full.html
+/// [`Layout`]: trait.Layout.html
+/// [`LayoutMessage::NextLayout`]: enum.LayoutMessage.html#variant.NextLayout
+/// [`Mirror`]: struct.Mirror.html
+/// [`Tall`]: struct.Tall.html
+/// [`Workspace`]: ../core/struct.Workspace.html
+pub fn default_layout<Window>() -> Box<dyn Layout<Window>>
+    where Window: Copy + Clone + Eq + Debug + 'static
+{
+    Box::new(Choose::new(vec![Box::new(Tall::default()),
+                              Box::new(Mirror::new(Box::new(Tall::default()))),
+                              Box::new(Full)]))
+}
+
+/// Messages that can be sent to a [`Layout`] to change its behaviour.
+/// Each [`Layout`] is free to ignore the messages it does not care about.
+///
+/// [`Layout`]: trait.Layout.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LayoutMessage {
+    /// Shrink the master area
+    Shrink,
+    /// Expand the master area
+    Expand,
+    /// Increase the number of windows in the master area
+    IncMaster,
+    /// Decrease the number of windows in the master area
+    DecMaster,
+    /// Switch to the next layout
+    NextLayout,
+    /// Switch to the previous layout
+    PrevLayout,
+    /// Switch back to the first layout
+    FirstLayout,
+}
+
+/// A general trait for all layouts. A [`Layout`] maps the windows
+/// of a [`Stack`] to their geometry on a screen.
+///
+/// # Immutable
+///
+/// Just like the rest of the window manager's state, layouts are
+/// immutable. Handling a [`LayoutMessage`] results in a new [`Layout`].
+///
+/// [`Layout`]: trait.Layout.html
+/// [`LayoutMessage`]: enum.LayoutMessage.html
+/// [`Stack`]: ../core/struct.Stack.html
+pub trait Layout<Window> {
+    /// Returns the name of the [`Layout`], e.g. to show it in a status bar
+    ///
+    /// [`Layout`]: trait.Layout.html
+    fn name(&self) -> String;
+
+    /// Computes the geometry for all windows in the given [`Stack`]
+    ///
+    /// # Arguments
+    /// `screen` - The [`Rectangle`] available for the windows
+    /// `stack` - The windows to arrange
+    ///
+    /// # Return value
+    /// A vector of all windows that shall be shown, together with
+    /// their [`Rectangle`]. Windows missing from the result are hidden.
+    ///
+    /// [`Rectangle`]: ../core/struct.Rectangle.html
+    /// [`Stack`]: ../core/struct.Stack.html
+    fn apply(&self, screen: Rectangle, stack: &Stack<Window>) -> Vec<(Window, Rectangle)>;
+
+    /// Reacts to the given [`LayoutMessage`]
+    ///
+    /// # Arguments
+    /// `message` - The [`LayoutMessage`] to handle
+    ///
+    /// # Return value
+    /// A new [`Layout`] if the message changed anything, `None` otherwise
+    ///
+    /// [`Layout`]: trait.Layout.html
+    /// [`LayoutMessage`]: enum.LayoutMessage.html
+    fn handle_message(&self, message: LayoutMessage) -> Option<Box<dyn Layout<Window>>>;
+
+    /// Creates a boxed copy of the [`Layout`]
+    ///
+    /// [`Layout`]: trait.Layout.html
+    fn copy(&self) -> Box<dyn Layout<Window>>;
+}
@@ -0,0 +1,184 @@
+use core::{Rectangle, Stack};
+use layout::{Layout, LayoutMessage};
+use std::fmt::Debug;
+
+/// The classic master/stack layout. The screen is split into a
+/// master column on the left and a column for all other windows
+/// on the right. Both columns are divided evenly between their windows.
+///
+/// * `master` is the number of windows in the master column
+/// * `ratio` is the fraction of the screen width used by the master column
+/// * `delta` is the step used to shrink or expand the master column
+///
+/// # Examples
+///
+/// ```
+/// # use sabiwm::core::{Rectangle, Stack};
+/// # use sabiwm::layout::{Layout, Tall};
+/// let tall = Tall::new(1, 0.5, 0.1);
+/// let screen = Rectangle::new(0, 0, 1920, 1080);
+///
+/// let single = Stack::from(1);
+/// assert_eq!(vec![(1, Rectangle::new(0, 0, 1920, 1080))],
+///            tall.apply(screen, &single));
+///
+/// let stack = Stack::new(1, vec![], vec![2, 3]);
+/// assert_eq!(vec![(1, Rectangle::new(0, 0, 960, 1080)),
+///                 (2, Rectangle::new(960, 0, 960, 540)),
+///                 (3, Rectangle::new(960, 540, 960, 540))],
+///            tall.apply(screen, &stack));
+/// ```
+///
+/// Pixels that can not be divided evenly are handed out to the
+/// first windows of a column, so no pixel is lost:
+///
+/// ```
+/// # use sabiwm::core::{Rectangle, Stack};
+/// # use sabiwm::layout::{Layout, Tall};
+/// let tall = Tall::new(2, 0.6, 0.1);
+/// let screen = Rectangle::new(10, 20, 1001, 601);
+/// let stack = Stack::new(1, vec![], vec![2, 3, 4, 5]);
+///
+/// assert_eq!(vec![(1, Rectangle::new(10, 20, 600, 301)),
+///                 (2, Rectangle::new(10, 321, 600, 300)),
+///                 (3, Rectangle::new(610, 20, 401, 201)),
+///                 (4, Rectangle::new(610, 221, 401, 200)),
+///                 (5, Rectangle::new(610, 421, 401, 200))],
+///            tall.apply(screen, &stack));
+/// ```
+///
+/// Sending [`LayoutMessage`]s adjusts the master column:
+///
+/// ```
+/// # use sabiwm::core::{Rectangle, Stack};
+/// # use sabiwm::layout::{Layout, LayoutMessage, Tall};
+/// let tall = Tall::new(1, 0.5, 0.25);
+/// let screen = Rectangle::new(0, 0, 800, 600);
+/// let stack = Stack::new(1, vec![], vec![2, 3]);
+///
+/// let expanded = Layout::<u32>::handle_message(&tall, LayoutMessage::Expand).unwrap();
+/// assert_eq!(vec![(1, Rectangle::new(0, 0, 600, 600)),
+///                 (2, Rectangle::new(600, 0, 200, 300)),
+///                 (3, Rectangle::new(600, 300, 200, 300))],
+///            expanded.apply(screen, &stack));
+///
+/// let shrunk = Layout::<u32>::handle_message(&tall, LayoutMessage::Shrink).unwrap();
+/// assert_eq!(vec![(1, Rectangle::new(0, 0, 200, 600)),
+///                 (2, Rectangle::new(200, 0, 600, 300)),
+///                 (3, Rectangle::new(200, 300, 600, 300))],
+///            shrunk.apply(screen, &stack));
+///
+/// let masters = Layout::<u32>::handle_message(&tall, LayoutMessage::IncMaster).unwrap();
+/// assert_eq!(vec![(1, Rectangle::new(0, 0, 400, 300)),
+///                 (2, Rectangle::new(0, 300, 400, 300)),
+///                 (3, Rectangle::new(400, 0, 400, 600))],
+///            masters.apply(screen, &stack));
+/// ```
+///
+/// The master column stays at least `delta` away from either
+/// screen edge, so it never disappears completely:
+///
+/// ```
+/// # use sabiwm::core::{Rectangle, Stack};
+/// # use sabiwm::layout::{Layout, LayoutMessage, Tall};
+/// let screen = Rectangle::new(0, 0, 800, 600);
+/// let stack = Stack::new(1, vec![], vec![2]);
+///
+/// let mut layout: Box<dyn Layout<u32>> = Box::new(Tall::new(1, 0.75, 0.25));
+/// while let Some(shrunk) = layout.handle_message(LayoutMessage::Shrink) {
+///     layout = shrunk;
+/// }
+/// assert_eq!(vec![(1, Rectangle::new(0, 0, 200, 600)),
+///                 (2, Rectangle::new(200, 0, 600, 600))],
+///            layout.apply(screen, &stack));
+///
+/// while let Some(expanded) = layout.handle_message(LayoutMessage::Expand) {
+///     layout = expanded;
+/// }
+/// assert_eq!(vec![(1, Rectangle::new(0, 0, 600, 600)),
+///                 (2, Rectangle::new(600, 0, 200, 600))],
+///            layout.apply(screen, &stack));
+/// ```
+///
+/// [`LayoutMessage`]: enum.LayoutMessage.html
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tall {
+    /// Number of windows in the master column
+    pub master: u32,
+    /// Fraction of the screen used by the master column
+    pub ratio: f32,
+    /// Step to shrink or expand the master column with
+    pub delta: f32,
+}
+
+impl Tall {
+    /// Create a new [`Tall`] layout
+    ///
+    /// # Arguments
+    /// `master` - Number of windows in the master column
+    /// `ratio` - Fraction of the screen used by the master column
+    /// `delta` - Step to shrink or expand the master column with
+    ///
+    /// [`Tall`]: struct.Tall.html
+    pub fn new(master: u32, ratio: f32, delta: f32) -> Tall {
+        Tall {
+            master,
+            ratio: ratio.clamp(0.0, 1.0),
+            delta,
+        }
+    }
+}
+
+impl Default for Tall {
+    fn default() -> Tall {
+        Tall::new(1, 0.5, 0.03)
+    }
+}
+
+impl<Window: Copy + Clone + Eq + Debug + 'static> Layout<Window> for Tall {
+    fn name(&self) -> String {
+        String::from("Tall")
+    }
+
+    fn apply(&self, screen: Rectangle, stack: &Stack<Window>) -> Vec<(Window, Rectangle)> {
+        let windows: Vec<Window> = stack.integrate();
+        let count = windows.len() as u32;
+
+        let rectangles = if self.master == 0 || count <= self.master {
+            screen.split_vertically(count)
+        } else {
+            let (master, slave) = screen.split_horizontally_by(self.ratio);
+            let mut rectangles = master.split_vertically(self.master);
+            rectangles.extend(slave.split_vertically(count - self.master));
+            rectangles
+        };
+
+        windows.into_iter().zip(rectangles).collect()
+    }
+
+    fn handle_message(&self, message: LayoutMessage) -> Option<Box<dyn Layout<Window>>> {
+        let tall = match message {
+            LayoutMessage::Shrink if self.ratio - self.delta >= self.delta => {
+                Tall::new(self.master, self.ratio - self.delta, self.delta)
+            }
+            LayoutMessage::Expand if self.ratio + self.delta <= 1.0 - self.delta => {
+                Tall::new(self.master, self.ratio + self.delta, self.delta)
+            }
+            LayoutMessage::IncMaster => Tall::new(self.master + 1, self.ratio, self.delta),
+            LayoutMessage::DecMaster => {
+                Tall::new(self.master.saturating_sub(1), self.ratio, self.delta)
+            }
+            _ => return None,
+        };
+
+        if tall == *self {
+            None
+        } else {
+            Some(Box::new(tall))
+        }
+    }
+
+    fn copy(&self) -> Box<dyn Layout<Window>> {
+        Box::new(*self)
+    }
+}
@@ -24,14 +24,39 @@
 //! The backend module contains the general backend trait to abstract
 //! away from all the different backends, e.g. XCB, Wayland, Redox and all the others out there.
 //!
+//! ### Layout
+//!
+//! The [`Layout`] module contains the trait to arrange windows on
+//! a screen, together with the messages to adjust layouts at runtime.
+//!
+//! ### Command
+//!
+//! A [`Command`] is everything the window manager can be told to do,
+//! no matter if it was triggered by a key binding, IPC or a script.
+//!
+//! ### Window Manager
+//!
+//! The [`WindowManager`] owns a backend together with the complete
+//! window manager state and drives both from the backend's events.
+//!
 //! ### Config
 //!
-//! The config module will contain several ways to configure the WM. All of them shall
+//! The [`Config`] module contains several ways to configure the WM. All of them shall
 //! be interchangeable. The first and easiest way is a simple TOML file and on top of that
 //! a ctl daemon, a LUA interface and direct IPC.
 //!
+//! ### IPC
+//!
+//! The [`IPC`] module lets scripts and status bars drive the window manager
+//! through JSON requests on a Unix socket.
+//!
 //! [`Core`]: core/index.html
+//! [`IPC`]: ipc/index.html
 //! ['Backend']: backend/index.html
+//! [`Command`]: command/enum.Command.html
+//! [`Config`]: config/index.html
+//! [`Layout`]: layout/index.html
+//! [`WindowManager`]: struct.WindowManager.html
 
 #![deny(missing_docs)]
 
@@ -45,50 +70,63 @@ extern crate xcb;
 extern crate xdg;
 #[macro_use]
 extern crate error_chain;
+extern crate inotify;
+extern crate nix;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_json;
+extern crate signal_hook;
+extern crate toml;
 
 #[macro_use]
 mod macros;
 pub mod backend;
+pub mod command;
+pub mod config;
 pub mod core;
+pub mod ipc;
+pub mod layout;
+mod log_hook;
+mod window_manager;
 
 mod errors {
     error_chain!{}
 }
 
 use errors::*;
-use backend::{Backend, Event};
+use backend::Backend;
+use config::Config;
 use std::fs::File;
 use slog::{Level, Logger, DrainExt, level_filter};
 use slog_stream::stream;
 use slog_scope::set_global_logger;
 use xdg::BaseDirectories;
 
+pub use window_manager::WindowManager;
+
 /// Run the actual window manager
 pub fn run() -> Result<()> {
     initialize_logger().chain_err(|| "unable to initialize logger")?;
 
+    let config = Config::load().chain_err(|| "unable to load config")?;
     let xcb = backend::Xcb::new()?;
-    let mut workspace: core::Workspace<u32> = core::Workspace::new(0, "Main", None);
+    let mut window_manager = WindowManager::new(xcb, config);
+
+    window_manager.run()
+}
 
-    loop {
-        match xcb.event() {
-            Event::WindowCreated(window) => {
-                if !xcb.is_window(window) {
-                    continue;
-                }
-                xcb.resize_window(window, 50, 50);
-                workspace = workspace.add(window);
-            }
-            Event::WindowClosed(window) => {
-                workspace = workspace.remove(window);
-            }
-            // Event::UnknownEvent => {
-            //    error!("unknown event");
-            //    bail!("unknown event type");
-            // }
-            _ => (),
-        }
+/// Validate the configuration file without starting the window manager
+///
+/// # Return value
+/// The path of the checked file, or an error pointing to the offending line and key
+pub fn check_config() -> Result<std::path::PathBuf> {
+    let path = config::path()?;
+    if path.exists() {
+        Config::from_file(&path)?;
     }
+    Ok(path)
 }
 
 /// Initialize the logger
@@ -0,0 +1,99 @@
+//! Writes the status line of the [`LogHook`] to its output.
+//! Writing never blocks the window manager: FIFOs are opened
+//! non-blocking and lines nobody is reading are dropped.
+//!
+//! [`LogHook`]: ../config/struct.LogHook.html
+
+use nix::libc;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::Path;
+
+/// The output of the status line, remembering the last line
+/// written, so unchanged lines are not written again
+pub(crate) struct LogWriter {
+    output: Option<String>,
+    fifo: Option<File>,
+    last: Option<String>,
+}
+
+impl LogWriter {
+    pub(crate) fn new() -> LogWriter {
+        LogWriter {
+            output: None,
+            fifo: None,
+            last: None,
+        }
+    }
+
+    /// Write the line to the given output, if it changed
+    ///
+    /// # Arguments
+    /// `output` - `"-"` for stdout, otherwise the path of a FIFO or a file
+    /// `line` - The status line without a trailing newline
+    pub(crate) fn write(&mut self, output: &str, line: String) {
+        if self.output.as_deref() != Some(output) {
+            self.output = Some(String::from(output));
+            self.fifo = None;
+            self.last = None;
+        }
+        if self.last.as_ref() == Some(&line) {
+            return;
+        }
+
+        trace!("writing status line {:?}", line);
+        let written = match output {
+            "-" => {
+                let stdout = io::stdout();
+                let mut stdout = stdout.lock();
+                writeln!(stdout, "{}", line).and_then(|_| stdout.flush())
+            }
+            path => self.write_file(Path::new(path), &line),
+        };
+
+        match written {
+            Ok(()) => self.last = Some(line),
+            Err(err) => debug!("unable to write status line to {}: {}", output, err),
+        }
+    }
+
+    /// Write the line into the FIFO or replace the content of the file
+    fn write_file(&mut self, path: &Path, line: &str) -> io::Result<()> {
+        let is_fifo = fs::metadata(path).map(|m| m.file_type().is_fifo()).unwrap_or(false);
+        if !is_fifo {
+            self.fifo = None;
+            return replace_file(path, line);
+        }
+
+        if self.fifo.is_none() {
+            // Fails if there is no reader yet, the line is
+            // written again with the next change then.
+            self.fifo = Some(OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(path)?);
+        }
+
+        let written = self.fifo.as_mut().map_or(Ok(()), |f| writeln!(f, "{}", line));
+        if written.is_err() {
+            // The reader went away or does not keep up
+            self.fifo = None;
+        }
+        written
+    }
+}
+
+/// Replace the content of the file with the line. The line is written
+/// to a temporary file next to it first, which is then renamed, so
+/// readers never see an empty or partially written file.
+fn replace_file(path: &Path, line: &str) -> io::Result<()> {
+    let name = path.file_name().ok_or_else(|| io::Error::other("not a file"))?;
+    let temporary = path.with_file_name(format!(".{}.tmp", name.to_string_lossy()));
+    let replaced = fs::write(&temporary, format!("{}\n", line))
+        .and_then(|_| fs::rename(&temporary, path));
+    if replaced.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    replaced
+}
@@ -1,4 +1,5 @@
 #[macro_export]
+/// Unwraps a `Result` or logs the error and returns `false`
 macro_rules! try_or_false {
     ($expr:expr) => (match $expr {
         Ok(val) => val,
@@ -0,0 +1,1641 @@
+//! The window manager ties the [`Backend`] and the core data
+//! structures together. It owns the backend and the complete
+//! window manager state, reacts to every [`Event`] the backend
+//! reports and translates the resulting state back into
+//! move/resize/show/hide/focus calls.
+//! Everything the user asks for arrives as a [`Command`].
+//!
+//! [`Backend`]: ../backend/trait.Backend.html
+//! [`Command`]: ../command/enum.Command.html
+//! [`Event`]: ../backend/enum.Event.html
+
+use backend::{Backend, Desktops, Event, KeyCommand, StateChange, WindowState, WindowType};
+use command::Command;
+use config::{self, Config, ConfigWatcher, WorkspaceState};
+use core::{Gravity, Rectangle, SizeHints, StackSet, Strut, Workspace};
+use errors::*;
+use ipc::{self, IpcServer, Notification, Reply, Request, WindowInfo, WorkspaceInfo};
+use ipc::i3::{self, I3Server, Message};
+use log_hook::LogWriter;
+use nix::errno::Errno;
+use nix::poll::{PollFd, PollFlags, PollTimeout, poll};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::env;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::os::unix::process::CommandExt;
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Set the configured layouts on all workspaces of the given [`StackSet`]
+///
+/// [`StackSet`]: ../core/struct.StackSet.html
+fn with_layouts<Window>(stack_set: StackSet<Window>, config: &Config) -> StackSet<Window>
+    where Window: Copy + Clone + PartialEq + Eq + Debug + Hash + 'static
+{
+    let ids = 0..stack_set.workspaces().len() as u32;
+    ids.fold(stack_set,
+             |s, id| s.map_workspace_by_id(id, |w| w.with_layout(config.layout())))
+}
+
+/// Run the given shell command in the background
+fn spawn(program: &str) {
+    debug!("spawning {:?}", program);
+    match process::Command::new("sh").arg("-c").arg(program).spawn() {
+        // Reap the child when it exits, so it does not linger as a zombie
+        Ok(mut child) => {
+            thread::spawn(move || child.wait());
+        }
+        Err(err) => error!("unable to spawn {:?}: {}", program, err),
+    }
+}
+
+/// Replace the running process with a new instance of the window manager
+fn restart() {
+    info!("restarting");
+    let err = env::current_exe()
+        .map(|exe| process::Command::new(exe).args(env::args_os().skip(1)).exec());
+    match err {
+        Ok(err) => error!("unable to restart: {}", err),
+        Err(err) => error!("unable to find own executable: {}", err),
+    }
+}
+
+/// The window manager state machine. It owns the [`Backend`]
+/// and the [`StackSet`] and keeps the two in sync.
+///
+/// # Immutable state
+///
+/// The [`StackSet`] itself is immutable. Each handler
+/// computes the new state from the old one and then calls
+/// `refresh` to push the result to the [`Backend`].
+///
+/// [`Backend`]: ../backend/trait.Backend.html
+/// [`StackSet`]: ../core/struct.StackSet.html
+pub struct WindowManager<B: Backend> {
+    backend: B,
+    stack_set: StackSet<B::Window>,
+    config: Config,
+    screens: Vec<Rectangle>,
+    clients: Vec<B::Window>,
+    ipc: Option<IpcServer>,
+    i3: Option<I3Server>,
+    observed: Observed<B::Window>,
+    urgent: HashSet<B::Window>,
+    fullscreen: HashSet<B::Window>,
+    docks: HashMap<B::Window, Vec<Strut>>,
+    desktop_windows: Vec<B::Window>,
+    struts_hidden: HashSet<u32>,
+    closing: HashMap<B::Window, Instant>,
+    size_hints: HashMap<B::Window, SizeHints>,
+    ignore_size_hints: HashSet<B::Window>,
+    transients: HashMap<B::Window, B::Window>,
+    geometry: HashMap<B::Window, Rectangle>,
+    stacking: Vec<(B::Window, bool)>,
+    log_writer: LogWriter,
+    running: bool,
+}
+
+/// The parts of the state that IPC clients get notified about,
+/// as they were when they were last published.
+struct Observed<Window> {
+    workspace: u32,
+    focus: Option<Window>,
+    layouts: HashMap<u32, String>,
+}
+
+impl<Window> Observed<Window>
+    where Window: Copy + Clone + PartialEq + Eq + Debug + Hash + 'static
+{
+    fn of(stack_set: &StackSet<Window>) -> Observed<Window> {
+        Observed {
+            workspace: stack_set.current.workspace.id,
+            focus: stack_set.peek(),
+            layouts: stack_set.workspaces()
+                .into_iter()
+                .map(|w| (w.id, w.layout.name()))
+                .collect(),
+        }
+    }
+}
+
+impl<B: Backend> WindowManager<B> {
+    /// Create a new window manager on top of the given [`Backend`]
+    ///
+    /// # Arguments
+    /// `backend` - The [`Backend`] to manage windows with
+    /// `config` - The [`Config`] to use
+    ///
+    /// # Return value
+    /// A new [`WindowManager`] with a set of empty workspaces
+    ///
+    /// [`Backend`]: ../backend/trait.Backend.html
+    /// [`Config`]: config/struct.Config.html
+    /// [`WindowManager`]: struct.WindowManager.html
+    pub fn new(backend: B, config: Config) -> WindowManager<B> {
+        let screens = backend.screens();
+        debug!("starting with screens {:?}", screens);
+
+        // Every screen needs a workspace, so add unnamed
+        // ones if there are not enough configured.
+        let mut tags = config.workspaces.clone();
+        for id in tags.len()..screens.len() {
+            tags.push((id + 1).to_string());
+        }
+
+        let stack_set = with_layouts(StackSet::new(tags, screens.len() as u32), &config);
+        let observed = Observed::of(&stack_set);
+
+        WindowManager {
+            backend,
+            stack_set,
+            config,
+            screens,
+            clients: Vec::new(),
+            ipc: None,
+            i3: None,
+            observed,
+            urgent: HashSet::new(),
+            fullscreen: HashSet::new(),
+            docks: HashMap::new(),
+            desktop_windows: Vec::new(),
+            struts_hidden: HashSet::new(),
+            closing: HashMap::new(),
+            size_hints: HashMap::new(),
+            ignore_size_hints: HashSet::new(),
+            transients: HashMap::new(),
+            geometry: HashMap::new(),
+            stacking: Vec::new(),
+            log_writer: LogWriter::new(),
+            running: true,
+        }
+    }
+
+    /// Returns a reference to the [`Backend`] used by this [`WindowManager`]
+    ///
+    /// [`Backend`]: ../backend/trait.Backend.html
+    /// [`WindowManager`]: struct.WindowManager.html
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the current window manager state
+    pub fn stack_set(&self) -> &StackSet<B::Window> {
+        &self.stack_set
+    }
+
+    /// Returns the currently focused [`Workspace`]
+    ///
+    /// [`Workspace`]: ../core/struct.Workspace.html
+    pub fn current_workspace(&self) -> &Workspace<B::Window> {
+        &self.stack_set.current.workspace
+    }
+
+    /// Run the event loop until the window manager is told to stop
+    ///
+    /// Besides the [`Backend`]'s events, this also watches for
+    /// requests to reload the configuration and serves IPC requests.
+    ///
+    /// [`Backend`]: ../backend/trait.Backend.html
+    pub fn run(&mut self) -> Result<()> {
+        self.grab_keys();
+        self.adopt_windows();
+        let path = match config::path() {
+            Ok(path) => Some(path),
+            Err(err) => {
+                warn!("not watching for config changes: {}", err);
+                None
+            }
+        };
+        let mut watcher = match ConfigWatcher::new(path.as_deref()) {
+            Ok(watcher) => Some(watcher),
+            Err(err) => {
+                error!("unable to listen for reload requests: {}", err);
+                None
+            }
+        };
+        self.ipc = match ipc::socket_path().and_then(|path| IpcServer::bind(&path)) {
+            Ok(server) => Some(server),
+            Err(err) => {
+                error!("IPC is not available: {}", err);
+                None
+            }
+        };
+        self.update_i3_ipc();
+
+        info!("entering event loop");
+        while self.running {
+            while let Some(event) = self.backend.poll_event() {
+                self.handle_event(event);
+            }
+            if !self.running {
+                break;
+            }
+
+            {
+                let mut fds = vec![PollFd::new(self.backend.as_fd(), PollFlags::POLLIN)];
+                if let Some(ref watcher) = watcher {
+                    fds.extend(watcher.fds().iter().map(|&fd| PollFd::new(fd, PollFlags::POLLIN)));
+                }
+                if let Some(ref ipc) = self.ipc {
+                    fds.extend(ipc.poll_fds());
+                }
+                if let Some(ref i3) = self.i3 {
+                    fds.extend(i3.poll_fds());
+                }
+
+                match poll(&mut fds, self.kill_deadline()) {
+                    Ok(_) | Err(Errno::EINTR) => (),
+                    Err(err) => bail!("unable to wait for events: {}", err),
+                }
+
+                let hangup = PollFlags::POLLHUP | PollFlags::POLLERR;
+                if fds[0].revents().is_some_and(|r| r.intersects(hangup)) {
+                    bail!("lost connection to the backend");
+                }
+            }
+
+            self.kill_unresponsive();
+            if watcher.as_mut().is_some_and(|w| w.reload_requested()) {
+                self.reload();
+            }
+            self.serve_ipc();
+            self.serve_i3_ipc();
+        }
+        info!("leaving event loop");
+        self.ipc = None;
+        self.stop_i3_ipc();
+        Ok(())
+    }
+
+    /// Manage all windows that existed before the window manager
+    /// started, putting them back on the workspace they were on
+    fn adopt_windows(&mut self) {
+        let windows = match self.backend.windows() {
+            Ok(windows) => windows,
+            Err(err) => return error!("unable to adopt existing windows: {}", err),
+        };
+
+        for window in windows {
+            if !self.backend.is_window(window) || self.backend.is_withdrawn(window) {
+                trace!("not adopting window {:?}", window);
+                continue;
+            }
+
+            let desktop = self.backend.window_desktop(window);
+            self.backend.adopt_window(window);
+            self.manage(window, desktop);
+        }
+        self.refresh();
+    }
+
+    /// Answer all pending IPC requests
+    fn serve_ipc(&mut self) {
+        let requests = match self.ipc {
+            Some(ref mut ipc) => ipc.requests(),
+            None => return,
+        };
+
+        for (client, request) in requests {
+            let reply = match request {
+                Ok(Request::Subscribe { events }) => {
+                    if let Some(ref mut ipc) = self.ipc {
+                        ipc.subscribe(client, &events);
+                    }
+                    Reply::Success
+                }
+                Ok(request) => self.handle_request(request),
+                Err(message) => Reply::Error { message },
+            };
+            if let Some(ref mut ipc) = self.ipc {
+                ipc.send(client, &reply);
+            }
+        }
+    }
+
+    /// Answer all pending messages on the i3 compatible socket
+    fn serve_i3_ipc(&mut self) {
+        let messages = match self.i3 {
+            Some(ref mut i3) => i3.messages(),
+            None => return,
+        };
+
+        for (client, message) in messages {
+            debug!("handling i3 IPC message {:?}", message);
+            let reply = match message {
+                Message::RunCommand(ref payload) => {
+                    let commands = i3::parse_commands(payload, &self.workspace_info());
+                    for command in &commands {
+                        if let Ok(Some(ref command)) = *command {
+                            self.execute(command.clone());
+                        }
+                    }
+                    i3::command_results(&commands)
+                }
+                Message::GetWorkspaces => i3::workspaces(&self.i3_state()),
+                Message::GetOutputs => i3::outputs(&self.i3_state()),
+                Message::GetTree => i3::tree(&self.i3_state()),
+            };
+            if let Some(ref mut i3) = self.i3 {
+                i3.reply(client, &message, &reply);
+            }
+        }
+    }
+
+    /// Start or stop the i3 compatible socket, as configured.
+    /// Like i3, the socket is announced through `I3SOCK` and
+    /// the `I3_SOCKET_PATH` root property.
+    fn update_i3_ipc(&mut self) {
+        if !self.config.i3_ipc {
+            return self.stop_i3_ipc();
+        }
+        if self.i3.is_some() {
+            return;
+        }
+
+        match i3::socket_path().and_then(|path| I3Server::bind(&path)) {
+            Ok(server) => {
+                let path = server.path().to_string_lossy().into_owned();
+                env::set_var("I3SOCK", &path);
+                self.backend.set_root_property("I3_SOCKET_PATH", Some(&path));
+                self.i3 = Some(server);
+            }
+            Err(err) => error!("i3 IPC is not available: {}", err),
+        }
+    }
+
+    fn stop_i3_ipc(&mut self) {
+        if self.i3.take().is_some() {
+            env::remove_var("I3SOCK");
+            self.backend.set_root_property("I3_SOCKET_PATH", None);
+        }
+    }
+
+    /// Collect everything needed to answer i3 IPC messages
+    fn i3_state(&self) -> i3::State<B::Window> {
+        let windows = self.stack_set
+            .workspaces()
+            .into_iter()
+            .flat_map(|w| w.windows())
+            .filter_map(|w| self.window_info(w))
+            .map(|window| {
+                let geometry = self.geometry
+                    .get(&window.id)
+                    .cloned()
+                    .unwrap_or_else(|| Rectangle::new(0, 0, 0, 0));
+                (window, geometry)
+            })
+            .collect();
+
+        i3::State {
+            workspaces: self.workspace_info(),
+            windows,
+            screens: self.screens.clone(),
+            focus: self.stack_set.peek(),
+        }
+    }
+
+    /// Handle a single IPC [`Request`]
+    ///
+    /// # Return value
+    /// The [`Reply`] to send back to the client
+    ///
+    /// [`Reply`]: ipc/enum.Reply.html
+    /// [`Request`]: ipc/enum.Request.html
+    pub fn handle_request(&mut self, request: Request) -> Reply<B::Window> {
+        debug!("handling IPC request {:?}", request);
+        match request {
+            Request::Command { command } => {
+                self.execute(command);
+                Reply::Success
+            }
+            Request::GetWorkspaces => Reply::Workspaces { workspaces: self.workspace_info() },
+            Request::GetFocused => {
+                Reply::Focused { window: self.stack_set.peek().and_then(|w| self.window_info(w)) }
+            }
+            Request::Subscribe { .. } => {
+                Reply::Error { message: String::from("subscriptions need an IPC connection") }
+            }
+        }
+    }
+
+    /// Send the [`Notification`] to all subscribed IPC clients
+    ///
+    /// [`Notification`]: ipc/enum.Notification.html
+    fn notify(&mut self, notification: Notification<B::Window>) {
+        if let Some(ref mut ipc) = self.ipc {
+            ipc.notify(&notification);
+        }
+        if let Some(ref mut i3) = self.i3 {
+            i3.notify(&notification, &self.screens);
+        }
+    }
+
+    /// Notify IPC clients about everything that changed
+    /// since the last time this was called
+    fn publish_changes(&mut self) {
+        let observed = Observed::of(&self.stack_set);
+
+        if observed.workspace != self.observed.workspace {
+            let workspace = self.workspace_info()
+                .into_iter()
+                .find(|w| w.id == observed.workspace);
+            if let Some(workspace) = workspace {
+                self.notify(Notification::WorkspaceChanged { workspace });
+            }
+        }
+
+        if observed.focus != self.observed.focus {
+            let window = observed.focus.and_then(|w| self.window_info(w));
+            self.notify(Notification::FocusChanged { window });
+        }
+
+        let mut layouts: Vec<_> = observed.layouts
+            .iter()
+            .filter(|&(id, name)| self.observed.layouts.get(id) != Some(name))
+            .map(|(&id, name)| (id, name.clone()))
+            .collect();
+        layouts.sort_by_key(|&(id, _)| id);
+        for (workspace, layout) in layouts {
+            self.notify(Notification::LayoutChanged { workspace, layout });
+        }
+
+        self.observed = observed;
+    }
+
+    /// Describe all workspaces, ordered by id
+    fn workspace_info(&self) -> Vec<WorkspaceInfo<B::Window>> {
+        let mut workspaces = self.stack_set.workspaces();
+        workspaces.sort_by_key(|w| w.id);
+        workspaces.into_iter()
+            .map(|workspace| {
+                WorkspaceInfo {
+                    id: workspace.id,
+                    tag: workspace.tag.clone(),
+                    layout: workspace.layout.name(),
+                    screen: self.stack_set
+                        .screens()
+                        .into_iter()
+                        .find(|s| s.workspace.id == workspace.id)
+                        .map(|s| s.screen_id),
+                    focused: self.stack_set.current.workspace.id == workspace.id,
+                    windows: workspace.windows(),
+                }
+            })
+            .collect()
+    }
+
+    /// Describe the given window, if it is managed
+    fn window_info(&self, window: B::Window) -> Option<WindowInfo<B::Window>> {
+        self.stack_set.find_workspace(window).map(|workspace| {
+            WindowInfo {
+                id: window,
+                name: self.backend.window_name(window).ok(),
+                class: self.backend.class_name(window).ok(),
+                workspace,
+                floating: self.stack_set.is_floating(window),
+            }
+        })
+    }
+
+    /// Reload the configuration file and apply it. All windows,
+    /// workspaces and the focus stay where they are. If the new
+    /// configuration is invalid, the current one is kept.
+    pub fn reload(&mut self) {
+        info!("reloading config");
+        match Config::load() {
+            Ok(config) => {
+                self.apply_config(config);
+                self.notify(Notification::ConfigReloaded);
+            }
+            Err(err) => {
+                error!("keeping current config: {}", err);
+                for err in err.iter().skip(1) {
+                    error!("caused by: {}", err);
+                }
+            }
+        }
+    }
+
+    /// Replace the current configuration, re-applying key bindings,
+    /// workspace names, borders, gaps and rules. Additional workspaces
+    /// are added hidden. Layouts are only replaced if the configured
+    /// layouts changed, otherwise the workspaces keep their current
+    /// layout state.
+    ///
+    /// # Arguments
+    /// `config` - The new [`Config`]
+    ///
+    /// [`Config`]: config/struct.Config.html
+    pub fn apply_config(&mut self, config: Config) {
+        let mut stack_set = self.stack_set.clone();
+        for (id, tag) in config.workspaces.iter().enumerate() {
+            stack_set = stack_set.map_workspace_by_id(id as u32, |w| {
+                Workspace { tag: tag.clone(), ..w }
+            });
+        }
+        let known = stack_set.workspaces().len();
+        if config.workspaces.len() < known {
+            warn!("removing workspaces only takes effect after a restart");
+        }
+        for (id, tag) in config.workspaces.iter().enumerate().skip(known) {
+            debug!("adding workspace {} {:?}", id, tag);
+            let workspace = Workspace::new(id as u32, tag.clone(), None);
+            stack_set.hidden.push(workspace.with_layout(config.layout()));
+        }
+        if config.layouts != self.config.layouts {
+            stack_set = with_layouts(stack_set, &config);
+        }
+
+        self.config = config;
+        self.stack_set = stack_set;
+        self.grab_keys();
+        self.update_i3_ipc();
+        self.refresh();
+    }
+
+    /// Dispatch a single [`Event`] to the matching handler
+    ///
+    /// # Arguments
+    /// `event` - The [`Event`] reported by the [`Backend`]
+    ///
+    /// [`Backend`]: ../backend/trait.Backend.html
+    /// [`Event`]: ../backend/enum.Event.html
+    pub fn handle_event(&mut self, event: Event<B::Window>) {
+        trace!("handling event {:?}", event);
+        match event {
+            Event::BackendChanged => self.backend_changed(),
+            Event::WindowCreated(window) => self.window_created(window),
+            Event::WindowClosed(window) => self.window_closed(window),
+            Event::WindowHid(window) => self.window_hid(window),
+            Event::WindowRevealed(window) => self.window_revealed(window),
+            Event::WindowTitleChanged(window) => self.window_title_changed(window),
+            Event::WindowHintsChanged(window) => self.window_hints_changed(window),
+            Event::StrutsChanged(window) => self.struts_changed(window),
+            Event::WindowChangeRequest(window, rectangle) => {
+                self.window_change_request(window, rectangle)
+            }
+            Event::MouseEnter(window) => self.mouse_enter(window),
+            Event::MouseLeave(window) => self.mouse_leave(window),
+            Event::ButtonPressed(window, subwindow) => self.button_pressed(window, subwindow),
+            Event::ButtonReleased => self.button_released(),
+            Event::KeyPressed(window, key) => self.key_pressed(window, key),
+            Event::ActivateRequest(window) => self.execute(Command::FocusWindow(window.into())),
+            Event::CloseRequest(window) => self.execute(Command::CloseWindow(window.into())),
+            Event::WorkspaceRequest(id) => self.execute(Command::View(id)),
+            Event::MoveRequest(window, id) => {
+                self.execute(Command::ShiftWindow(window.into(), id))
+            }
+            Event::StateRequest(window, change, state) => {
+                self.execute(Command::SetWindowState(window.into(), change, state))
+            }
+            Event::Unknown => (),
+        }
+    }
+
+    /// Apply the given function to the [`StackSet`] and
+    /// push the new state to the [`Backend`]
+    ///
+    /// [`Backend`]: ../backend/trait.Backend.html
+    /// [`StackSet`]: ../core/struct.StackSet.html
+    pub fn windows<F>(&mut self, f: F)
+        where F: FnOnce(&StackSet<B::Window>) -> StackSet<B::Window>
+    {
+        self.stack_set = f(&self.stack_set);
+        self.refresh();
+    }
+
+    /// Bind the given key combination to a [`Command`],
+    /// replacing any [`Command`] previously bound to it.
+    /// The keys are grabbed when the event loop starts.
+    ///
+    /// # Arguments
+    /// `spec` - The key combination, e.g. `"Mod4+Shift+Return"`
+    /// `command` - The [`Command`] to execute when the keys are pressed
+    ///
+    /// # Return value
+    /// An error if the key combination can't be parsed
+    ///
+    /// [`Command`]: command/enum.Command.html
+    pub fn add_key_binding(&mut self, spec: &str, command: Command) -> Result<()> {
+        let key = spec.parse::<KeyCommand>()?;
+        trace!("binding key {} to {}", key, command);
+        self.config.keys.insert(key, command);
+        Ok(())
+    }
+
+    /// Execute the given [`Command`]. This is the one place where
+    /// commands are interpreted, no matter if they were triggered
+    /// by a key binding, the mouse or any other source.
+    ///
+    /// [`Command`]: command/enum.Command.html
+    pub fn execute(&mut self, command: Command) {
+        debug!("executing command {}", command);
+        if let Some(stack_set) = command.apply(&self.stack_set) {
+            self.stack_set = stack_set;
+            self.refresh();
+            return;
+        }
+
+        match command {
+            Command::Kill => {
+                if let Some(window) = self.stack_set.peek() {
+                    self.close_window(window);
+                }
+            }
+            Command::CloseWindow(window) => self.close_window(B::Window::from(window)),
+            Command::SetWindowState(window, change, state) => {
+                self.set_window_state(B::Window::from(window), change, state)
+            }
+            Command::Spawn(program) => spawn(&program),
+            Command::ToggleStruts => {
+                let id = self.stack_set.current.workspace.id;
+                if !self.struts_hidden.remove(&id) {
+                    self.struts_hidden.insert(id);
+                }
+                self.refresh();
+            }
+            Command::Quit => {
+                info!("quitting");
+                if let Some(ref mut i3) = self.i3 {
+                    i3.shutdown("exit");
+                }
+                self.running = false;
+            }
+            Command::Restart => {
+                if let Some(ref mut i3) = self.i3 {
+                    i3.shutdown("restart");
+                }
+                restart();
+            }
+            Command::Reload => self.reload(),
+            _ => (),
+        }
+    }
+
+    /// Removes all key bindings
+    pub fn clear_key_bindings(&mut self) {
+        self.config.keys.clear();
+    }
+
+    /// Tell the [`Backend`] to grab all bound keys
+    ///
+    /// [`Backend`]: ../backend/trait.Backend.html
+    pub fn grab_keys(&self) {
+        let keys: Vec<KeyCommand> = self.config.keys.keys().cloned().collect();
+        self.backend.grab_keys(&keys);
+    }
+
+    fn is_managed(&self, window: B::Window) -> bool {
+        self.stack_set.contains(window)
+    }
+
+    fn backend_changed(&mut self) {
+        self.screens = self.backend.screens();
+        debug!("screens changed to {:?}", self.screens);
+        let screens = self.screens.len() as u32;
+        self.windows(|s| s.rescreen(screens));
+        self.notify(Notification::ScreensChanged { screens: self.screens.clone() });
+    }
+
+    fn window_created(&mut self, window: B::Window) {
+        if self.manage(window, None) {
+            self.refresh();
+        }
+    }
+
+    /// Classify a new window and start managing it,
+    /// without pushing the new state to the [`Backend`]
+    ///
+    /// # Arguments
+    /// `window` - The new window
+    /// `workspace` - The workspace the window should be placed on,
+    ///               unless it is a transient
+    ///
+    /// # Return value
+    /// `true` if the window is managed now, or is a dock or desktop
+    ///
+    /// [`Backend`]: ../backend/trait.Backend.html
+    fn manage(&mut self, window: B::Window, workspace: Option<u32>) -> bool {
+        if !self.backend.is_window(window) || self.is_managed(window) ||
+           self.docks.contains_key(&window) || self.desktop_windows.contains(&window) {
+            trace!("not managing window {:?}", window);
+            return false;
+        }
+
+        let window_type = self.backend.window_type(window);
+        let name = self.backend.window_name(window).ok();
+        let class = self.backend.class_name(window).ok();
+        let rule = self.config.rule(class.as_deref(), name.as_deref(), window_type).cloned();
+        if let Some(ref rule) = rule {
+            debug!("applying rule {:?} to window {:?}", rule, window);
+        }
+
+        let manage = rule.as_ref().and_then(|r| r.manage);
+        match window_type {
+            WindowType::Dock if manage != Some(true) => {
+                debug!("not managing dock {:?}", window);
+                self.docks.insert(window, self.backend.struts(window));
+                return true;
+            }
+            WindowType::Desktop if manage != Some(true) => {
+                debug!("not managing desktop {:?}", window);
+                self.desktop_windows.push(window);
+                return true;
+            }
+            _ if !manage.unwrap_or_else(|| window_type.is_managed()) => {
+                debug!("not managing {:?} window {:?}", window_type, window);
+                return false;
+            }
+            _ => (),
+        }
+        debug!("managing {:?} window {:?} ({:?}, {:?})",
+               window_type,
+               window,
+               name,
+               class);
+
+        let hints = self.backend.size_hints(window);
+        let parent = self.parent_of(window);
+        let mut stack_set = self.stack_set.insert(window);
+        if let Some(id) = workspace.or_else(|| rule.as_ref().and_then(|r| r.workspace)) {
+            stack_set = stack_set.shift_window(id, window);
+        }
+        if rule.as_ref().is_some_and(|r| r.ignore_size_hints) {
+            self.ignore_size_hints.insert(window);
+        }
+
+        // Transients stay on the workspace of their parent
+        if let Some(parent) = parent {
+            debug!("window {:?} is transient for {:?}", window, parent);
+            if let Some(id) = stack_set.find_workspace(parent) {
+                stack_set = stack_set.shift_window(id, window);
+            }
+            self.transients.insert(window, parent);
+        }
+
+        let float = rule.as_ref()
+            .and_then(|r| r.float)
+            .unwrap_or_else(|| window_type.floats() || hints.is_fixed() || parent.is_some());
+        if float {
+            match self.backend.window_geometry(window) {
+                Ok(geometry) => {
+                    let geometry = match parent {
+                        Some(parent) => self.center_on_parent(&stack_set, geometry, parent),
+                        None => geometry,
+                    };
+                    stack_set = stack_set.float(window, geometry);
+                }
+                Err(err) => error!("unable to float window {:?}: {}", window, err),
+            }
+        }
+
+        self.stack_set = stack_set;
+        self.size_hints.insert(window, hints);
+        self.clients.push(window);
+        if let Some(window) = self.window_info(window) {
+            self.notify(Notification::WindowManaged { window });
+        }
+        true
+    }
+
+    /// Centre a new transient over its parent, within the usable
+    /// area of the parent's screen. After that, the transient only
+    /// moves along with its parent.
+    ///
+    /// # Arguments
+    /// `stack_set` - The state the transient is added to
+    /// `rectangle` - The current geometry of the transient
+    /// `parent` - The window the transient belongs to
+    ///
+    /// # Return value
+    /// The geometry of the transient, unchanged if the parent wasn't placed yet
+    fn center_on_parent(&self,
+                        stack_set: &StackSet<B::Window>,
+                        rectangle: Rectangle,
+                        parent: B::Window)
+                        -> Rectangle {
+        let centered = match self.geometry.get(&parent) {
+            Some(parent) => rectangle.center_on(parent),
+            None => return rectangle,
+        };
+        match stack_set.screen_of(parent).and_then(|id| self.screens.get(id as usize)) {
+            Some(screen) => centered.clamp(&self.usable_area(screen)),
+            None => centered,
+        }
+    }
+
+    fn window_closed(&mut self, window: B::Window) {
+        if self.docks.remove(&window).is_some() {
+            debug!("dock {:?} is gone", window);
+            self.refresh();
+            return;
+        }
+        if self.desktop_windows.contains(&window) {
+            debug!("desktop {:?} is gone", window);
+            self.desktop_windows.retain(|&w| w != window);
+            return;
+        }
+        if !self.is_managed(window) {
+            return;
+        }
+
+        debug!("unmanaging window {:?}", window);
+        self.closing.remove(&window);
+        self.size_hints.remove(&window);
+        self.ignore_size_hints.remove(&window);
+        self.urgent.remove(&window);
+        self.fullscreen.remove(&window);
+        self.geometry.remove(&window);
+        self.clients.retain(|&w| w != window);
+        self.transients.retain(|_, &mut parent| parent != window);
+
+        // The focus returns from a transient to its parent
+        let mut stack_set = self.stack_set.delete(window);
+        let parent = self.transients.remove(&window);
+        if let Some(parent) = parent.filter(|_| self.stack_set.peek() == Some(window)) {
+            stack_set = stack_set.focus_window(parent);
+        }
+        self.windows(|_| stack_set);
+        self.notify(Notification::WindowUnmanaged { window });
+    }
+
+    /// The window the given one is transient for. Transients
+    /// for the root window or for no window at all belong to
+    /// the most recent main window of their group.
+    fn parent_of(&self, window: B::Window) -> Option<B::Window> {
+        let parent = self.backend.transient_for(window)?;
+        if parent != window && self.is_managed(parent) {
+            return Some(parent);
+        }
+
+        let group = self.backend.window_group(window)?;
+        self.clients
+            .iter()
+            .rev()
+            .cloned()
+            .filter(|&w| w != window && !self.transients.contains_key(&w))
+            .find(|&w| self.backend.window_group(w) == Some(group))
+    }
+
+    /// Move transients that got separated from their
+    /// parent back to the parent's workspace
+    fn keep_transients_with_parents(&mut self) {
+        // Parents are moved first, so nested transients follow them
+        let mut transients: Vec<(B::Window, B::Window)> =
+            self.transients.iter().map(|(&w, &p)| (w, p)).collect();
+        transients.sort_by_key(|&(w, _)| self.layer(w));
+
+        let mut stack_set = self.stack_set.clone();
+        for (window, parent) in transients {
+            if let Some(id) = stack_set.find_workspace(parent) {
+                if stack_set.find_workspace(window) != Some(id) {
+                    trace!("moving window {:?} along with {:?}", window, parent);
+                    stack_set = stack_set.shift_window(id, window);
+                }
+            }
+        }
+        self.stack_set = stack_set;
+    }
+
+    /// The stacking layer of the window. Tiled windows are at
+    /// the bottom, floating windows above them and transients
+    /// above their parents.
+    fn layer(&self, window: B::Window) -> usize {
+        let mut layer = if self.stack_set.is_floating(window) { 1 } else { 0 };
+        let mut current = window;
+        // Bounded in case of clients naming each other as parent
+        while let Some(&parent) = self.transients.get(&current) {
+            if layer > self.transients.len() {
+                break;
+            }
+            layer += 1;
+            current = parent;
+        }
+        layer
+    }
+
+    fn window_hid(&mut self, window: B::Window) {
+        trace!("window {:?} got hidden", window);
+        // Hidden docks don't take up any space
+        if let Some(struts) = self.docks.get_mut(&window) {
+            struts.clear();
+            self.refresh();
+        }
+    }
+
+    fn window_revealed(&mut self, window: B::Window) {
+        trace!("window {:?} got revealed", window);
+        if self.docks.contains_key(&window) {
+            self.struts_changed(window);
+        }
+    }
+
+    fn window_title_changed(&mut self, window: B::Window) {
+        if !self.is_managed(window) {
+            return;
+        }
+
+        if let Ok(name) = self.backend.window_name(window) {
+            trace!("window {:?} is now called {}", window, name);
+            self.notify(Notification::TitleChanged { window, name });
+        }
+        self.log();
+    }
+
+    fn window_hints_changed(&mut self, window: B::Window) {
+        if !self.is_managed(window) {
+            return;
+        }
+
+        // The focused window already has the user's attention
+        if self.backend.is_urgent(window) && self.stack_set.peek() != Some(window) {
+            debug!("window {:?} demands attention", window);
+            self.urgent.insert(window);
+        } else {
+            self.urgent.remove(&window);
+        }
+
+        let hints = self.backend.size_hints(window);
+        if self.size_hints.insert(window, hints) != Some(hints) {
+            trace!("window {:?} supports new sizes", window);
+            self.refresh();
+        } else {
+            self.log();
+        }
+    }
+
+    fn window_change_request(&mut self, window: B::Window, rectangle: Rectangle) {
+        if self.is_managed(window) && !self.stack_set.is_floating(window) {
+            // Tiled windows get their geometry from the window manager,
+            // so simply enforce the current one again.
+            trace!("ignoring change request of managed window {:?}", window);
+            self.refresh();
+        } else if self.is_managed(window) {
+            trace!("moving floating window {:?} to {:?}", window, rectangle);
+            // Floating rectangles include the border
+            let border = 2 * self.config.border.width;
+            let rectangle = Rectangle::new(rectangle.x(),
+                                           rectangle.y(),
+                                           rectangle.width() + border,
+                                           rectangle.height() + border);
+            self.windows(|s| s.float(window, rectangle));
+        } else {
+            trace!("granting change request of window {:?} to {:?}",
+                   window,
+                   rectangle);
+            self.backend.move_window(window, rectangle.x(), rectangle.y());
+            self.backend.resize_window(window, rectangle.width(), rectangle.height());
+        }
+    }
+
+    fn mouse_enter(&mut self, window: Option<B::Window>) {
+        if let Some(window) = window {
+            self.focus(window);
+        }
+    }
+
+    fn mouse_leave(&mut self, window: Option<B::Window>) {
+        trace!("mouse left window {:?}", window);
+    }
+
+    fn button_pressed(&mut self, window: B::Window, subwindow: Option<B::Window>) {
+        self.focus(subwindow.unwrap_or(window));
+    }
+
+    fn button_released(&mut self) {
+        trace!("button released");
+    }
+
+    fn key_pressed(&mut self, window: B::Window, key: KeyCommand) {
+        trace!("key {} pressed on window {:?}", key, window);
+        match self.config.keys.get(&key).cloned() {
+            Some(command) => self.execute(command),
+            None => debug!("no binding for key {}", key),
+        }
+    }
+
+    fn set_window_state(&mut self, window: B::Window, change: StateChange, state: WindowState) {
+        if !self.is_managed(window) {
+            return;
+        }
+
+        let states = match state {
+            WindowState::Fullscreen => &mut self.fullscreen,
+            WindowState::DemandsAttention => &mut self.urgent,
+        };
+        let set = match change {
+            StateChange::Remove => false,
+            StateChange::Add => true,
+            StateChange::Toggle => !states.contains(&window),
+        };
+        debug!("setting {:?} of window {:?} to {}", state, window, set);
+        if set {
+            states.insert(window);
+        } else {
+            states.remove(&window);
+        }
+        self.refresh();
+    }
+
+    fn struts_changed(&mut self, window: B::Window) {
+        if !self.docks.contains_key(&window) {
+            return;
+        }
+
+        let struts = self.backend.struts(window);
+        if self.docks.insert(window, struts.clone()).as_ref() != Some(&struts) {
+            self.refresh();
+        }
+    }
+
+    /// Ask the given window to close. If a kill timeout is
+    /// configured, asking a second time kills the window.
+    fn close_window(&mut self, window: B::Window) {
+        if !self.is_managed(window) {
+            return;
+        }
+
+        if self.config.kill_timeout.is_some() && self.closing.contains_key(&window) {
+            info!("window {:?} was asked to close twice, killing it", window);
+            self.closing.remove(&window);
+            self.backend.kill_window(window);
+        } else {
+            self.closing.insert(window, Instant::now());
+            self.backend.close_window(window);
+        }
+    }
+
+    /// How long to wait for events until the next window
+    /// that was asked to close has to be killed
+    fn kill_deadline(&self) -> PollTimeout {
+        let timeout = match self.config.kill_timeout {
+            Some(timeout) => Duration::from_secs(timeout),
+            None => return PollTimeout::NONE,
+        };
+        match self.closing.values().map(|since| timeout.saturating_sub(since.elapsed())).min() {
+            // Round up, so the deadline has passed after waiting
+            Some(left) => {
+                PollTimeout::try_from(left.as_millis() as u64 + 1).unwrap_or(PollTimeout::MAX)
+            }
+            None => PollTimeout::NONE,
+        }
+    }
+
+    /// Kill all windows that did not close within the kill timeout
+    fn kill_unresponsive(&mut self) {
+        let timeout = match self.config.kill_timeout {
+            Some(timeout) => Duration::from_secs(timeout),
+            None => return,
+        };
+        let unresponsive: Vec<B::Window> = self.closing
+            .iter()
+            .filter(|&(_, since)| since.elapsed() >= timeout)
+            .map(|(&window, _)| window)
+            .collect();
+        for window in unresponsive {
+            info!("window {:?} did not close in time, killing it", window);
+            self.closing.remove(&window);
+            self.backend.kill_window(window);
+        }
+    }
+
+    /// Move the focus to the given window, if it is visible
+    fn focus(&mut self, window: B::Window) {
+        if self.stack_set.screen_of(window).is_none() || self.stack_set.peek() == Some(window) {
+            return;
+        }
+
+        self.execute(Command::FocusWindow(window.into()));
+    }
+
+    /// Push the current state to the [`Backend`]
+    /// and notify IPC clients about the changes.
+    ///
+    /// [`Backend`]: ../backend/trait.Backend.html
+    fn refresh(&mut self) {
+        self.keep_transients_with_parents();
+        self.arrange();
+        self.publish_desktops();
+        if let Some(window) = self.stack_set.peek() {
+            self.urgent.remove(&window);
+        }
+        self.publish_changes();
+        self.log();
+    }
+
+    /// Let the [`Backend`] publish the workspaces and windows
+    /// for pagers and bars
+    ///
+    /// [`Backend`]: ../backend/trait.Backend.html
+    fn publish_desktops(&self) {
+        let mut workspaces = self.stack_set.workspaces();
+        workspaces.sort_by_key(|w| w.id);
+        // There is only one work area for all screens, so it
+        // leaves out the space reserved on any of them
+        let workarea = self.display()
+            .map(|display| self.usable_area(&display))
+            .unwrap_or_else(|| Rectangle::new(0, 0, 0, 0));
+
+        let mut stacking: Vec<B::Window> =
+            self.clients.iter().cloned().filter(|&w| self.is_managed(w)).collect();
+        stacking.sort_by_key(|&w| self.layer(w));
+
+        let desktops = Desktops {
+            names: workspaces.into_iter().map(|w| w.tag.clone()).collect(),
+            current: self.stack_set.current.workspace.id,
+            workarea,
+            clients: self.clients
+                .iter()
+                .filter_map(|&w| self.stack_set.find_workspace(w).map(|id| (w, id)))
+                .collect(),
+            stacking,
+            active: self.stack_set.peek(),
+            states: self.clients
+                .iter()
+                .flat_map(|&w| {
+                    let fullscreen = Some(WindowState::Fullscreen)
+                        .filter(|_| self.fullscreen.contains(&w));
+                    let urgent = Some(WindowState::DemandsAttention)
+                        .filter(|_| self.urgent.contains(&w));
+                    fullscreen.into_iter().chain(urgent).map(move |s| (w, s))
+                })
+                .collect(),
+        };
+        self.backend.publish_desktops(&desktops);
+    }
+
+    /// Write the status line of the configured [`LogHook`], if it changed
+    ///
+    /// [`LogHook`]: config/struct.LogHook.html
+    fn log(&mut self) {
+        let output = match self.config.log_hook.output {
+            Some(ref output) => output.clone(),
+            None => return,
+        };
+
+        let mut workspaces = self.stack_set.workspaces();
+        workspaces.sort_by_key(|w| w.id);
+        let visible: Vec<u32> = self.stack_set.screens().iter().map(|s| s.workspace.id).collect();
+        let workspaces: Vec<(&str, WorkspaceState)> = workspaces.into_iter()
+            .map(|workspace| {
+                let windows = workspace.windows();
+                let state = if workspace.id == self.stack_set.current.workspace.id {
+                    WorkspaceState::Current
+                } else if windows.iter().any(|w| self.urgent.contains(w)) {
+                    WorkspaceState::Urgent
+                } else if visible.contains(&workspace.id) {
+                    WorkspaceState::Visible
+                } else if !windows.is_empty() {
+                    WorkspaceState::Hidden
+                } else {
+                    WorkspaceState::Empty
+                };
+                (workspace.tag.as_str(), state)
+            })
+            .collect();
+
+        let layout = self.stack_set.current.workspace.layout.name();
+        let title = self.stack_set
+            .peek()
+            .and_then(|w| self.backend.window_name(w).ok())
+            .unwrap_or_default();
+        let line = self.config.log_hook.format(&workspaces, &layout, &title);
+        self.log_writer.write(&output, line);
+    }
+
+    /// Fit the window into the given [`Rectangle`] according to its
+    /// [`SizeHints`]. Tiled windows are centred in their slot,
+    /// floating windows are placed by their gravity.
+    ///
+    /// [`Rectangle`]: ../core/struct.Rectangle.html
+    /// [`SizeHints`]: ../core/struct.SizeHints.html
+    fn apply_size_hints(&self, window: B::Window, rectangle: &Rectangle) -> Rectangle {
+        let hints = match self.size_hints.get(&window) {
+            Some(hints) => hints,
+            None => return *rectangle,
+        };
+
+        if self.stack_set.is_floating(window) {
+            hints.apply(rectangle, hints.gravity)
+        } else if self.ignore_size_hints.contains(&window) {
+            *rectangle
+        } else {
+            hints.apply(rectangle, Gravity::Center)
+        }
+    }
+
+    /// The part of the screen that is not reserved by docks
+    ///
+    /// # Arguments
+    /// `screen` - The area of the whole screen
+    fn usable_area(&self, screen: &Rectangle) -> Rectangle {
+        self.docks
+            .values()
+            .flat_map(|struts| struts.iter())
+            .fold(*screen, |area, strut| strut.apply(&area))
+    }
+
+    /// The area covered by all screens together
+    fn display(&self) -> Option<Rectangle> {
+        self.screens
+            .iter()
+            .fold(None, |area: Option<Rectangle>, s| Some(area.map_or(*s, |a| a.union(s))))
+    }
+
+    /// How far the parent of a floating transient moved since
+    /// the last time it was placed
+    ///
+    /// # Arguments
+    /// `window` - The window that might be a floating transient
+    /// `placed` - The windows placed so far and their new geometry
+    ///
+    /// # Return value
+    /// The horizontal and vertical distance, if the parent moved
+    fn parent_offset(&self,
+                     window: B::Window,
+                     placed: &[(B::Window, Rectangle)])
+                     -> Option<(i32, i32)> {
+        let parent = self.transients.get(&window).filter(|_| self.stack_set.is_floating(window))?;
+        let old = self.geometry.get(parent)?;
+        let new = placed.iter().find(|&&(w, _)| w == *parent).map(|&(_, r)| r)?;
+        Some((new.x() - old.x(), new.y() - old.y())).filter(|&offset| offset != (0, 0))
+    }
+
+    /// Recompute and remember the geometry of all windows
+    /// and issue the necessary calls to the [`Backend`].
+    ///
+    /// [`Backend`]: ../backend/trait.Backend.html
+    fn arrange(&mut self) {
+        trace!("refreshing window geometry");
+        let mut placed = Vec::new();
+        let mut stacking = Vec::new();
+        let mut moved_transients = Vec::new();
+        // Desktop windows cover all screens, below everything else
+        let display = self.display();
+        for &window in &self.desktop_windows {
+            if let Some(display) = display {
+                self.backend.move_window(window, display.x(), display.y());
+                self.backend.resize_window(window, display.width(), display.height());
+            }
+            self.backend.lower_window(window);
+        }
+
+        for workspace in &self.stack_set.hidden {
+            for window in workspace.windows() {
+                self.backend.hide_window(window);
+            }
+        }
+
+        for screen in self.stack_set.screens() {
+            let full_area = match self.screens.get(screen.screen_id as usize) {
+                Some(&area) => area,
+                None => continue,
+            };
+
+            let workspace = &screen.workspace;
+            let area = if self.struts_hidden.contains(&workspace.id) {
+                full_area
+            } else {
+                self.usable_area(&full_area)
+            };
+            let gap = self.config.gap;
+            let tiled = workspace.stack
+                .clone()
+                .and_then(|s| s.filter(|w| !self.stack_set.is_floating(*w)))
+                .map_or(Vec::new(), |s| workspace.layout.apply(area, &s))
+                .into_iter()
+                .map(|(w, r)| (w, r.shrink(gap)));
+            let floating = workspace.windows()
+                .into_iter()
+                .filter_map(|w| self.stack_set.floating.get(&w).map(|&r| (w, r)));
+
+            // The focused window of a workspace covers the
+            // whole screen while it is in fullscreen mode
+            let fullscreen = workspace.stack
+                .as_ref()
+                .map(|s| s.focus)
+                .filter(|w| self.fullscreen.contains(w));
+            let arranged: Vec<_> = match fullscreen {
+                Some(window) => vec![(window, full_area)],
+                None => {
+                    let mut arranged: Vec<_> = tiled.chain(floating).collect();
+                    arranged.sort_by_key(|&(w, _)| self.layer(w));
+                    arranged
+                }
+            };
+            for window in workspace.windows() {
+                if !arranged.iter().any(|&(w, _)| w == window) {
+                    self.backend.hide_window(window);
+                }
+            }
+
+            let border = &self.config.border;
+            // Parents come first, so nested transients
+            // follow their parent after it was moved
+            for (window, rectangle) in arranged {
+                let rectangle = match self.parent_offset(window, &placed) {
+                    Some((x, y)) => {
+                        let moved = Rectangle::new(rectangle.x() + x,
+                                                   rectangle.y() + y,
+                                                   rectangle.width(),
+                                                   rectangle.height())
+                            .clamp(&area);
+                        moved_transients.push((window, moved));
+                        moved
+                    }
+                    None => rectangle,
+                };
+                let color = if self.stack_set.peek() == Some(window) {
+                    border.focused
+                } else {
+                    border.normal
+                };
+                let width = if fullscreen.is_some() { 0 } else { border.width };
+                // The border is drawn around the window,
+                // so it has to fit into the rectangle as well.
+                let size = |s: u32| s.saturating_sub(2 * width).max(1);
+                let inner = Rectangle::new(rectangle.x(),
+                                           rectangle.y(),
+                                           size(rectangle.width()),
+                                           size(rectangle.height()));
+                let rectangle = match fullscreen {
+                    Some(_) => inner,
+                    None => self.apply_size_hints(window, &inner),
+                };
+                self.backend.set_window_border(window, width, color.0);
+                self.backend.move_window(window, rectangle.x(), rectangle.y());
+                self.backend
+                    .resize_window(window, rectangle.width().max(1), rectangle.height().max(1));
+                self.backend.show_window(window);
+                stacking.push((window, fullscreen.is_some() || self.layer(window) > 0));
+                placed.push((window, rectangle));
+            }
+        }
+
+        // Restacking makes windows flicker, so it only
+        // happens if the order of the windows changed
+        if stacking != self.stacking {
+            for &(window, raise) in &stacking {
+                if raise {
+                    self.backend.raise_window(window);
+                }
+            }
+            self.stacking = stacking;
+        }
+        // Remembered for IPC clients, hidden windows keep their last geometry
+        self.geometry.extend(placed);
+        for (window, rectangle) in moved_transients {
+            self.stack_set = self.stack_set.float(window, rectangle);
+        }
+
+        if let Some(window) = self.stack_set.peek() {
+            self.backend.focus_window(window);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use backend::{Event, KeyCommand, MockBackend, StateChange, WindowState, WindowType};
+    use command::Command;
+    use config::{Border, Config};
+    use core::{Edge, Rectangle, Strut};
+    use ipc::{Reply, Request};
+    use serde_json;
+    use super::WindowManager;
+
+    /// A window manager on a single 800x600 screen, without borders
+    fn window_manager() -> WindowManager<MockBackend> {
+        let backend = MockBackend::with_screens(vec![Rectangle::new(0, 0, 800, 600)]);
+        let border = Border { width: 0, ..Border::default() };
+        let config = Config { border, ..Config::default() };
+        WindowManager::new(backend, config)
+    }
+
+    /// A window manager already managing the given windows
+    fn with_windows(windows: &[u32]) -> WindowManager<MockBackend> {
+        let mut window_manager = window_manager();
+        for &window in windows {
+            window_manager.handle_event(Event::WindowCreated(window));
+        }
+        window_manager
+    }
+
+    #[test]
+    fn new_windows_are_tiled_and_focused() {
+        let window_manager = with_windows(&[1, 2]);
+        let backend = window_manager.backend();
+
+        assert_eq!(Rectangle::new(0, 0, 400, 600), backend.geometry_of(2));
+        assert_eq!(Rectangle::new(400, 0, 400, 600), backend.geometry_of(1));
+        assert!(backend.is_visible(1) && backend.is_visible(2));
+        assert_eq!(Some(2), backend.focused.get());
+        assert_eq!(Some(2), window_manager.stack_set().peek());
+    }
+
+    #[test]
+    fn closed_windows_are_unmanaged() {
+        let mut window_manager = with_windows(&[1, 2]);
+        window_manager.handle_event(Event::WindowClosed(2));
+
+        assert!(!window_manager.stack_set().contains(2));
+        assert_eq!(Rectangle::new(0, 0, 800, 600),
+                   window_manager.backend().geometry_of(1));
+        assert_eq!(Some(1), window_manager.backend().focused.get());
+    }
+
+    #[test]
+    fn hidden_windows_stay_managed() {
+        let mut window_manager = with_windows(&[1]);
+        window_manager.handle_event(Event::WindowHid(1));
+
+        assert!(window_manager.stack_set().contains(1));
+    }
+
+    #[test]
+    fn windows_of_other_workspaces_are_hidden() {
+        let mut window_manager = with_windows(&[1]);
+
+        window_manager.execute(Command::View(1));
+        assert_eq!(1, window_manager.current_workspace().id);
+        assert!(!window_manager.backend().is_visible(1));
+
+        window_manager.execute(Command::View(0));
+        assert!(window_manager.backend().is_visible(1));
+    }
+
+    #[test]
+    fn key_bindings_execute_their_command() {
+        let mut window_manager = with_windows(&[1]);
+        window_manager.clear_key_bindings();
+        window_manager.add_key_binding("Mod4+x", Command::View(2)).unwrap();
+
+        let key = "Mod4+x".parse::<KeyCommand>().unwrap();
+        window_manager.handle_event(Event::KeyPressed(1, key));
+        assert_eq!(2, window_manager.current_workspace().id);
+    }
+
+    #[test]
+    fn the_mouse_focuses_windows() {
+        let mut window_manager = with_windows(&[1, 2, 3]);
+
+        window_manager.handle_event(Event::MouseEnter(Some(1)));
+        assert_eq!(Some(1), window_manager.backend().focused.get());
+
+        window_manager.handle_event(Event::ButtonPressed(0, Some(2)));
+        assert_eq!(Some(2), window_manager.backend().focused.get());
+
+        // Clicks on the root window keep the focus
+        window_manager.handle_event(Event::ButtonPressed(0, None));
+        assert_eq!(Some(2), window_manager.stack_set().peek());
+    }
+
+    #[test]
+    fn docks_reserve_space() {
+        let mut window_manager = window_manager();
+        window_manager.backend().window_types.borrow_mut().insert(5, WindowType::Dock);
+        window_manager.backend()
+            .struts
+            .borrow_mut()
+            .insert(5, vec![Strut::new(Edge::Top, Rectangle::new(0, 0, 800, 20))]);
+
+        window_manager.handle_event(Event::WindowCreated(5));
+        window_manager.handle_event(Event::WindowCreated(1));
+
+        let area = Rectangle::new(0, 20, 800, 580);
+        assert!(!window_manager.stack_set().contains(5));
+        assert_eq!(area, window_manager.backend().geometry_of(1));
+        let desktops = window_manager.backend().desktops.borrow().clone().unwrap();
+        assert_eq!(area, desktops.workarea);
+
+        window_manager.execute(Command::ToggleStruts);
+        assert_eq!(Rectangle::new(0, 0, 800, 600),
+                   window_manager.backend().geometry_of(1));
+    }
+
+    #[test]
+    fn transients_open_centred_and_follow_their_parent() {
+        let mut window_manager = with_windows(&[1]);
+        window_manager.backend().transients.borrow_mut().insert(2, 1);
+        window_manager.backend().geometry.borrow_mut().insert(2, Rectangle::new(0, 0, 200, 100));
+
+        window_manager.handle_event(Event::WindowCreated(2));
+        assert!(window_manager.stack_set().is_floating(2));
+        assert_eq!(Rectangle::new(300, 250, 200, 100),
+                   window_manager.backend().geometry_of(2));
+
+        // Moved by the client, it is not centred again
+        window_manager.handle_event(Event::WindowChangeRequest(2,
+                                                               Rectangle::new(10, 10, 200, 100)));
+        assert_eq!(Rectangle::new(10, 10, 200, 100),
+                   window_manager.backend().geometry_of(2));
+
+        // The new window becomes the master, moving the parent to the right
+        window_manager.handle_event(Event::WindowCreated(3));
+        assert_eq!(Rectangle::new(400, 0, 400, 600),
+                   window_manager.backend().geometry_of(1));
+        assert_eq!(Rectangle::new(410, 10, 200, 100),
+                   window_manager.backend().geometry_of(2));
+
+        window_manager.execute(Command::FocusWindow(2));
+        window_manager.handle_event(Event::WindowClosed(2));
+        assert_eq!(Some(1), window_manager.stack_set().peek());
+    }
+
+    #[test]
+    fn change_requests_of_tiled_windows_are_ignored() {
+        let mut window_manager = with_windows(&[1]);
+        window_manager.handle_event(Event::WindowChangeRequest(1, Rectangle::new(5, 5, 50, 50)));
+
+        assert_eq!(Rectangle::new(0, 0, 800, 600),
+                   window_manager.backend().geometry_of(1));
+    }
+
+    #[test]
+    fn change_requests_of_unmanaged_windows_are_granted() {
+        let mut window_manager = window_manager();
+        let rectangle = Rectangle::new(-10, -20, 30, 40);
+        window_manager.handle_event(Event::WindowChangeRequest(7, rectangle));
+
+        assert_eq!(rectangle, window_manager.backend().geometry_of(7));
+    }
+
+    #[test]
+    fn fullscreen_windows_cover_their_screen() {
+        let mut window_manager = with_windows(&[1, 2]);
+        let fullscreen = Event::StateRequest(2, StateChange::Toggle, WindowState::Fullscreen);
+
+        window_manager.handle_event(fullscreen);
+        assert_eq!(Rectangle::new(0, 0, 800, 600),
+                   window_manager.backend().geometry_of(2));
+        assert!(!window_manager.backend().is_visible(1));
+
+        window_manager.handle_event(fullscreen);
+        assert_eq!(Rectangle::new(0, 0, 400, 600),
+                   window_manager.backend().geometry_of(2));
+        assert!(window_manager.backend().is_visible(1));
+    }
+
+    #[test]
+    fn client_messages_are_executed() {
+        let mut window_manager = with_windows(&[1, 2]);
+
+        window_manager.handle_event(Event::ActivateRequest(1));
+        assert_eq!(Some(1), window_manager.stack_set().peek());
+
+        window_manager.handle_event(Event::MoveRequest(1, 3));
+        assert_eq!(Some(3), window_manager.stack_set().find_workspace(1));
+        assert!(!window_manager.backend().is_visible(1));
+
+        window_manager.handle_event(Event::WorkspaceRequest(3));
+        assert_eq!(3, window_manager.current_workspace().id);
+
+        window_manager.handle_event(Event::CloseRequest(1));
+        assert_eq!(vec![1], *window_manager.backend().closed.borrow());
+    }
+
+    #[test]
+    fn unmanaged_windows_can_not_be_closed() {
+        let mut window_manager = with_windows(&[1]);
+        window_manager.handle_event(Event::CloseRequest(9));
+
+        assert!(window_manager.backend().closed.borrow().is_empty());
+    }
+
+    #[test]
+    fn reloading_the_config_keeps_the_windows() {
+        let mut window_manager = with_windows(&[1, 2]);
+        window_manager.execute(Command::FocusWindow(1));
+
+        let workspaces = (1..12).map(|i| i.to_string()).collect();
+        let config = Config { gap: 10, workspaces, ..window_manager.config.clone() };
+        window_manager.apply_config(config);
+
+        assert_eq!(Some(1), window_manager.stack_set().peek());
+        assert_eq!(Rectangle::new(10, 10, 380, 580),
+                   window_manager.backend().geometry_of(2));
+        assert_eq!(11, window_manager.stack_set().workspaces().len());
+    }
+
+    #[test]
+    fn ipc_requests_are_answered() {
+        let mut window_manager = with_windows(&[1]);
+        let request = r#"{"type": "command", "command": "view 4"}"#;
+        let request: Request = serde_json::from_str(request).unwrap();
+
+        assert_eq!(Reply::Success, window_manager.handle_request(request));
+        assert_eq!(4, window_manager.current_workspace().id);
+
+        match window_manager.handle_request(Request::GetWorkspaces) {
+            Reply::Workspaces { workspaces } => {
+                assert_eq!(9, workspaces.len());
+                assert_eq!(vec![1], workspaces[0].windows);
+                assert!(workspaces[4].focused);
+            }
+            reply => panic!("unexpected reply {:?}", reply),
+        }
+    }
+}